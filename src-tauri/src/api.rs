@@ -1,3 +1,4 @@
+use crate::speaker::AudioEncoding;
 use base64::{engine::general_purpose, Engine as _};
 use futures_util::StreamExt;
 use reqwest::multipart::{Form, Part};
@@ -174,23 +175,52 @@ pub struct UserAudioConfig {
     headers: Option<Vec<UserAudioHeader>>,
 }
 
-// Audio API Command
+// Audio API Command. Takes a segment ID from a speech event, or base64 audio.
 #[tauri::command]
 pub async fn transcribe_audio(
     app: AppHandle,
-    audio_base64: String,
+    segment_id: Option<u64>,
+    audio_base64: Option<String>,
 ) -> Result<AudioResponse, String> {
-    let (_, _, selected_model) = get_stored_credentials(&app).await?;
+    let segment;
+    let decoded;
+    let audio_bytes: &[u8] = match (segment_id, audio_base64) {
+        (Some(segment_id), _) => {
+            segment = crate::speaker::segment_audio(&app, segment_id)?;
+            &segment
+        }
+        (None, Some(audio_base64)) => {
+            decoded = decode_audio_base64(&audio_base64)?;
+            &decoded
+        }
+        (None, None) => return Err("No audio provided".to_string()),
+    };
+
+    let transcription = transcribe_audio_bytes(&app, audio_bytes).await?;
+    Ok(AudioResponse {
+        success: true,
+        transcription: Some(transcription),
+        error: None,
+    })
+}
+
+// Transcribes one encoded segment with the local model or the configured endpoints
+pub async fn transcribe_audio_bytes(app: &AppHandle, audio_bytes: &[u8]) -> Result<String, String> {
+    // A selected local model handles everything offline
+    if let Some(transcription) = crate::local_stt::transcribe(app, audio_bytes).await? {
+        return Ok(transcription);
+    }
+
+    let (_, _, selected_model) = get_stored_credentials(app).await?;
     let provider = selected_model.as_ref().map(|model| model.provider.clone());
     let model = selected_model.as_ref().map(|model| model.model.clone());
 
-    let api_config = fetch_api_response_config(&app, provider.clone(), model.clone()).await?;
+    let api_config = fetch_api_response_config(app, provider.clone(), model.clone()).await?;
     let user_audio_config = api_config.user_audio.as_ref().ok_or_else(|| {
         "Audio transcription is not configured for this workspace. Please contact support."
             .to_string()
     })?;
 
-    let audio_bytes = decode_audio_base64(&audio_base64)?;
     let client = reqwest::Client::new();
     let error_provider = provider.clone();
     let error_model = model.clone();
@@ -200,15 +230,11 @@ pub async fn transcribe_audio(
         &user_audio_config.user_token,
         &user_audio_config.model,
         user_audio_config.headers.as_ref(),
-        &audio_bytes,
+        audio_bytes,
     )
     .await
     {
-        Ok(transcription) => Ok(AudioResponse {
-            success: true,
-            transcription: Some(transcription),
-            error: None,
-        }),
+        Ok(transcription) => Ok(transcription),
         Err(primary_error) => {
             let fallback_error_message = if let (Some(fallback_url), Some(fallback_token)) = (
                 user_audio_config.fallback_url.as_ref(),
@@ -225,17 +251,11 @@ pub async fn transcribe_audio(
                     fallback_token,
                     fallback_model,
                     user_audio_config.headers.as_ref(),
-                    &audio_bytes,
+                    audio_bytes,
                 )
                 .await
                 {
-                    Ok(transcription) => {
-                        return Ok(AudioResponse {
-                            success: true,
-                            transcription: Some(transcription),
-                            error: None,
-                        });
-                    }
+                    Ok(transcription) => return Ok(transcription),
                     Err(fallback_error) => Some(fallback_error),
                 }
             } else {
@@ -392,9 +412,11 @@ async fn perform_user_audio_transcription(
     headers: Option<&Vec<UserAudioHeader>>,
     audio_bytes: &[u8],
 ) -> Result<String, String> {
+    // Segments may be WAV, FLAC or Ogg Opus depending on VadConfig::encoding
+    let encoding = AudioEncoding::detect(audio_bytes).unwrap_or_default();
     let audio_part = Part::bytes(audio_bytes.to_vec())
-        .file_name("audio.wav")
-        .mime_str("audio/wav")
+        .file_name(encoding.file_name())
+        .mime_str(encoding.mime_type())
         .map_err(|e| format!("Failed to prepare audio payload: {}", e))?;
 
     let mut form = Form::new()
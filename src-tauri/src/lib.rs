@@ -3,8 +3,11 @@ mod activate;
 mod api;
 mod capture;
 mod db;
+mod local_stt;
 mod shortcuts;
 mod window;
+use std::collections::HashMap;
+use std::sync::atomic::AtomicBool;
 use std::sync::{Arc, Mutex};
 use tauri::Manager;
 #[cfg(target_os = "macos")]
@@ -13,7 +16,9 @@ use tauri_plugin_posthog::{init as posthog_init, PostHogConfig, PostHogOptions};
 use tokio::task::JoinHandle;
 mod speaker;
 use capture::CaptureState;
-use speaker::VadConfig;
+use speaker::{
+    AudioSource, CaptureMonitor, MeetingRecording, RealtimeSession, SegmentStore, VadConfig,
+};
 
 #[cfg(target_os = "macos")]
 #[allow(deprecated)]
@@ -22,8 +27,14 @@ use tauri_nspanel::{cocoa::appkit::NSWindowCollectionBehavior, panel_delegate, W
 #[derive(Default)]
 pub struct AudioState {
     stream_task: Arc<Mutex<Option<JoinHandle<()>>>>,
+    mic_task: Arc<Mutex<Option<JoinHandle<()>>>>,
     vad_config: Arc<Mutex<VadConfig>>,
     is_capturing: Arc<Mutex<bool>>,
+    capture_monitors: Arc<Mutex<HashMap<AudioSource, Arc<CaptureMonitor>>>>,
+    realtime_session: Arc<Mutex<Option<RealtimeSession>>>,
+    meeting_recording: Arc<Mutex<Option<Arc<MeetingRecording>>>>,
+    segments: SegmentStore,
+    audio_imports: Arc<Mutex<HashMap<u64, Arc<AtomicBool>>>>, // Cancel flags by import ID
 }
 
 #[tauri::command]
@@ -44,6 +55,7 @@ pub fn run() {
         )
         .manage(AudioState::default())
         .manage(CaptureState::default())
+        .manage(local_stt::LocalSttState::default())
         .manage(shortcuts::RegisteredShortcuts::default())
         .manage(shortcuts::LicenseState::default())
         .manage(shortcuts::MoveWindowState::default())
@@ -114,12 +126,35 @@ pub fn run() {
             speaker::start_system_audio_capture,
             speaker::stop_system_audio_capture,
             speaker::manual_stop_continuous,
+            speaker::pause_continuous,
+            speaker::resume_continuous,
             speaker::check_system_audio_access,
             speaker::request_system_audio_access,
             speaker::get_vad_config,
             speaker::update_vad_config,
             speaker::get_capture_status,
             speaker::get_audio_sample_rate,
+            speaker::get_audio_diagnostics,
+            speaker::list_audio_devices,
+            speaker::start_mic_capture,
+            speaker::stop_mic_capture,
+            speaker::start_dual_capture,
+            speaker::stop_dual_capture,
+            speaker::start_realtime_transcription,
+            speaker::stop_realtime_transcription,
+            speaker::start_meeting_recording,
+            speaker::stop_meeting_recording,
+            speaker::list_meeting_recordings,
+            speaker::delete_meeting_recording,
+            speaker::get_audio_segment,
+            speaker::release_audio_segment,
+            speaker::import_audio_file,
+            speaker::cancel_audio_import,
+            local_stt::local_stt_available,
+            local_stt::list_local_stt_models,
+            local_stt::download_local_stt_model,
+            local_stt::delete_local_stt_model,
+            local_stt::set_local_stt_model,
         ])
         .setup(|app| {
             // Setup main window positioning
@@ -127,6 +162,14 @@ pub fn run() {
             #[cfg(target_os = "macos")]
             init(app.app_handle());
 
+            // Return audio a crashed run left on its per-application capture sink
+            #[cfg(target_os = "linux")]
+            std::thread::spawn(|| {
+                if let Err(e) = speaker::unload_stale_routes() {
+                    eprintln!("Failed to clean up stale audio routes: {}", e);
+                }
+            });
+
             let app_handle = app.handle();
             if app_handle.get_webview_window("dashboard").is_none() {
                 if let Err(e) = window::create_dashboard_window(app_handle) {
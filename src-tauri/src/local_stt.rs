@@ -0,0 +1,382 @@
+// Meetwings offline speech-to-text: whisper.cpp models kept in the app data directory.
+// Inference needs the `local-stt` cargo feature; model management works in every build.
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter, Manager};
+
+const MODEL_BASE_URL: &str = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main";
+const KNOWN_MODELS: &[&str] = &[
+    "tiny",
+    "tiny.en",
+    "base",
+    "base.en",
+    "small",
+    "small.en",
+    "medium",
+    "medium.en",
+    "large-v3-turbo",
+];
+
+// Loaded model, reused across transcriptions
+#[derive(Default)]
+pub struct LocalSttState {
+    #[cfg(feature = "local-stt")]
+    context: tokio::sync::Mutex<Option<(String, std::sync::Arc<whisper_rs::WhisperContext>)>>,
+}
+
+// Persisted in local_stt.json; a selected model routes transcribe_audio to whisper
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct LocalSttSettings {
+    model: Option<String>,
+    language: Option<String>, // None = auto-detect
+}
+
+#[derive(Debug, Serialize)]
+pub struct LocalSttModel {
+    name: String,
+    downloaded: bool,
+    size_bytes: Option<u64>,
+    selected: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct DownloadProgress {
+    name: String,
+    downloaded: u64,
+    total: Option<u64>,
+}
+
+fn models_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data directory: {}", e))?
+        .join("models")
+        .join("whisper");
+
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create models directory: {}", e))?;
+    Ok(dir)
+}
+
+// Only known names are accepted, so they are safe to use as file names
+fn model_path(app: &AppHandle, name: &str) -> Result<PathBuf, String> {
+    if !KNOWN_MODELS.contains(&name) {
+        return Err(format!("Unknown local model: {}", name));
+    }
+    Ok(models_dir(app)?.join(format!("ggml-{}.bin", name)))
+}
+
+fn settings_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(models_dir(app)?.join("local_stt.json"))
+}
+
+fn load_settings(app: &AppHandle) -> Result<LocalSttSettings, String> {
+    let path = settings_path(app)?;
+    if !path.exists() {
+        return Ok(LocalSttSettings::default());
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read local STT settings: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse local STT settings: {}", e))
+}
+
+fn save_settings(app: &AppHandle, settings: &LocalSttSettings) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize local STT settings: {}", e))?;
+    fs::write(settings_path(app)?, content)
+        .map_err(|e| format!("Failed to write local STT settings: {}", e))
+}
+
+// Whether this build can run models locally
+#[tauri::command]
+pub fn local_stt_available() -> bool {
+    cfg!(feature = "local-stt")
+}
+
+#[tauri::command]
+pub async fn list_local_stt_models(app: AppHandle) -> Result<Vec<LocalSttModel>, String> {
+    let selected = load_settings(&app)?.model;
+
+    KNOWN_MODELS
+        .iter()
+        .map(|&name| {
+            let size_bytes = fs::metadata(model_path(&app, name)?).ok().map(|m| m.len());
+            Ok(LocalSttModel {
+                name: name.to_string(),
+                downloaded: size_bytes.is_some(),
+                size_bytes,
+                selected: selected.as_deref() == Some(name),
+            })
+        })
+        .collect()
+}
+
+// Downloads a model, emitting `local-stt-download-progress` as it goes
+#[tauri::command]
+pub async fn download_local_stt_model(app: AppHandle, name: String) -> Result<(), String> {
+    let path = model_path(&app, &name)?;
+    let partial = path.with_extension("bin.part");
+
+    let response = reqwest::get(format!("{}/ggml-{}.bin", MODEL_BASE_URL, name))
+        .await
+        .map_err(|e| format!("Failed to download model: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Failed to download model: {}", response.status()));
+    }
+
+    let total = response.content_length();
+    let mut file =
+        fs::File::create(&partial).map_err(|e| format!("Failed to create model file: {}", e))?;
+    let mut downloaded = 0u64;
+    let mut last_percent = None;
+    let mut body = response.bytes_stream();
+
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk.map_err(|e| {
+            let _ = fs::remove_file(&partial);
+            format!("Model download interrupted: {}", e)
+        })?;
+        file.write_all(&chunk)
+            .map_err(|e| format!("Failed to write model file: {}", e))?;
+        downloaded += chunk.len() as u64;
+
+        // One event per percent (or per chunk when the size is unknown)
+        let percent = total.map(|total| downloaded * 100 / total.max(1));
+        if percent.is_none() || percent != last_percent {
+            last_percent = percent;
+            let _ = app.emit(
+                "local-stt-download-progress",
+                DownloadProgress {
+                    name: name.clone(),
+                    downloaded,
+                    total,
+                },
+            );
+        }
+    }
+
+    file.sync_all()
+        .map_err(|e| format!("Failed to write model file: {}", e))?;
+    drop(file);
+    fs::rename(&partial, &path).map_err(|e| format!("Failed to save model file: {}", e))
+}
+
+#[tauri::command]
+pub async fn delete_local_stt_model(app: AppHandle, name: String) -> Result<(), String> {
+    let path = model_path(&app, &name)?;
+
+    let mut settings = load_settings(&app)?;
+    if settings.model.as_deref() == Some(name.as_str()) {
+        settings.model = None;
+        save_settings(&app, &settings)?;
+    }
+    unload_model(&app).await;
+
+    if path.exists() {
+        fs::remove_file(&path).map_err(|e| format!("Failed to delete model: {}", e))?;
+    }
+    Ok(())
+}
+
+// Selects the model transcribe_audio should use; None goes back to the cloud endpoint
+#[tauri::command]
+pub async fn set_local_stt_model(
+    app: AppHandle,
+    name: Option<String>,
+    language: Option<String>,
+) -> Result<(), String> {
+    if let Some(name) = name.as_deref() {
+        if !local_stt_available() {
+            return Err("Local transcription is not available in this build".to_string());
+        }
+        if !model_path(&app, name)?.exists() {
+            return Err(format!("Model {} has not been downloaded", name));
+        }
+    }
+
+    save_settings(
+        &app,
+        &LocalSttSettings {
+            model: name,
+            language,
+        },
+    )?;
+    unload_model(&app).await;
+    Ok(())
+}
+
+// Transcribes a WAV or FLAC segment (e.g. a `speech-detected` payload) when a local model is
+// selected. Ok(None) means no model is usable and the cloud endpoint should be used.
+pub async fn transcribe(app: &AppHandle, audio: &[u8]) -> Result<Option<String>, String> {
+    let settings = load_settings(app)?;
+    // A model saved by a build with local STT is ignored by one without it
+    let Some(model) = settings.model.filter(|_| local_stt_available()) else {
+        return Ok(None);
+    };
+
+    run_model(app, &model, settings.language, audio)
+        .await
+        .map(Some)
+}
+
+#[cfg(feature = "local-stt")]
+async fn run_model(
+    app: &AppHandle,
+    model: &str,
+    language: Option<String>,
+    audio: &[u8],
+) -> Result<String, String> {
+    use whisper_rs::{FullParams, SamplingStrategy};
+
+    const WHISPER_SAMPLE_RATE: u32 = 16_000;
+
+    let samples = decode_audio(audio, WHISPER_SAMPLE_RATE)?;
+    let context = load_model(app, model).await?;
+
+    tokio::task::spawn_blocking(move || {
+        let mut state = context
+            .create_state()
+            .map_err(|e| format!("Failed to create whisper state: {}", e))?;
+
+        let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+        params.set_language(Some(language.as_deref().unwrap_or("auto")));
+        params.set_n_threads(
+            std::thread::available_parallelism()
+                .map(|n| n.get().min(8) as i32)
+                .unwrap_or(4),
+        );
+        params.set_print_progress(false);
+        params.set_print_realtime(false);
+        params.set_print_special(false);
+        params.set_print_timestamps(false);
+
+        state
+            .full(params, &samples)
+            .map_err(|e| format!("Local transcription failed: {}", e))?;
+
+        let segments = state
+            .full_n_segments()
+            .map_err(|e| format!("Local transcription failed: {}", e))?;
+        let mut text = String::new();
+        for i in 0..segments {
+            let segment = state
+                .full_get_segment_text(i)
+                .map_err(|e| format!("Local transcription failed: {}", e))?;
+            text.push_str(&segment);
+        }
+        Ok(text.trim().to_string())
+    })
+    .await
+    .map_err(|e| format!("Local transcription task failed: {}", e))?
+}
+
+#[cfg(not(feature = "local-stt"))]
+async fn run_model(
+    _app: &AppHandle,
+    _model: &str,
+    _language: Option<String>,
+    _audio: &[u8],
+) -> Result<String, String> {
+    Err("Local transcription is not available in this build".to_string())
+}
+
+#[cfg(feature = "local-stt")]
+async fn load_model(
+    app: &AppHandle,
+    name: &str,
+) -> Result<std::sync::Arc<whisper_rs::WhisperContext>, String> {
+    use whisper_rs::{WhisperContext, WhisperContextParameters};
+
+    let state = app.state::<LocalSttState>();
+    let mut cached = state.context.lock().await;
+    if let Some((loaded, context)) = cached.as_ref() {
+        if loaded == name {
+            return Ok(context.clone());
+        }
+    }
+
+    let path = model_path(app, name)?;
+    let context = tokio::task::spawn_blocking(move || {
+        WhisperContext::new_with_params(
+            &path.to_string_lossy(),
+            WhisperContextParameters::default(),
+        )
+        .map_err(|e| format!("Failed to load model {}: {}", path.display(), e))
+    })
+    .await
+    .map_err(|e| format!("Failed to load model: {}", e))??;
+
+    let context = std::sync::Arc::new(context);
+    *cached = Some((name.to_string(), context.clone()));
+    Ok(context)
+}
+
+async fn unload_model(_app: &AppHandle) {
+    #[cfg(feature = "local-stt")]
+    {
+        *_app.state::<LocalSttState>().context.lock().await = None;
+    }
+}
+
+// Mono f32 at `sample_rate` from any PCM WAV or FLAC
+#[cfg(feature = "local-stt")]
+fn decode_audio(audio: &[u8], sample_rate: u32) -> Result<Vec<f32>, String> {
+    use crate::speaker::AudioEncoding;
+    use hound::{SampleFormat, WavReader};
+
+    let (samples, channels, source_rate): (Vec<f32>, u16, u32) = match AudioEncoding::detect(audio)
+    {
+        Some(AudioEncoding::Wav) => {
+            let mut reader = WavReader::new(std::io::Cursor::new(audio))
+                .map_err(|e| format!("Failed to read audio: {}", e))?;
+            let spec = reader.spec();
+
+            let samples = match spec.sample_format {
+                SampleFormat::Float => reader
+                    .samples::<f32>()
+                    .collect::<Result<_, _>>()
+                    .map_err(|e| format!("Failed to read audio: {}", e))?,
+                SampleFormat::Int => {
+                    let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+                    reader
+                        .samples::<i32>()
+                        .map(|s| s.map(|s| s as f32 / scale))
+                        .collect::<Result<_, _>>()
+                        .map_err(|e| format!("Failed to read audio: {}", e))?
+                }
+            };
+            (samples, spec.channels, spec.sample_rate)
+        }
+        Some(AudioEncoding::Flac) => {
+            let mut reader = claxon::FlacReader::new(std::io::Cursor::new(audio))
+                .map_err(|e| format!("Failed to read audio: {}", e))?;
+            let info = reader.streaminfo();
+
+            let scale = (1i64 << (info.bits_per_sample - 1)) as f32;
+            let samples = reader
+                .samples()
+                .map(|s| s.map(|s| s as f32 / scale))
+                .collect::<Result<_, _>>()
+                .map_err(|e| format!("Failed to read audio: {}", e))?;
+            (samples, info.channels as u16, info.sample_rate)
+        }
+        _ => {
+            return Err(
+                "Local transcription needs WAV or FLAC audio; change the segment encoding"
+                    .to_string(),
+            )
+        }
+    };
+
+    let channels = channels.max(1) as usize;
+    let mono: Vec<f32> = samples
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect();
+
+    Ok(crate::speaker::resample(&mono, source_rate, sample_rate))
+}
@@ -1,19 +1,51 @@
 // Meetwings AI Speech Detection, and capture system audio (speaker output) as a stream of f32 samples.
-use crate::speaker::SpeakerInput;
+use crate::speaker::denoise::NoiseSuppressor;
+use crate::speaker::encode::{self, AudioEncoding};
+use crate::speaker::import::{cancel_import, start_import, SplitEvent, UtteranceSplitter};
+use crate::speaker::realtime::{stream_transcription, RealtimeProvider, RealtimeSttConfig};
+use crate::speaker::recording::{
+    delete_recording, list_recordings, MeetingRecording, MeetingRecordingInfo,
+};
+use crate::speaker::resample::resample;
+use crate::speaker::segments::SegmentInfo;
+use crate::speaker::spectrum::PowerSpectrum;
+use crate::speaker::{
+    AudioDevice, AudioDiagnostics, AudioFrame, CaptureMonitor, ChannelMode, DeviceEvent, Downmix,
+    MicInput, NoiseEstimate, SpeakerInput,
+};
 use anyhow::Result;
-use base64::{engine::general_purpose::STANDARD as B64, Engine as _};
-use futures_util::StreamExt;
-use hound::{WavSpec, WavWriter};
+use futures_util::{Stream, StreamExt};
 use serde::{Deserialize, Serialize};
-use std::collections::VecDeque;
-use std::io::Cursor;
-use std::sync::atomic::{AtomicBool, Ordering};
+use serde_json::json;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tauri::{AppHandle, Emitter, Listener, Manager};
+use tauri::{AppHandle, Emitter, Listener, Manager, Runtime};
 use tauri_plugin_shell::ShellExt;
+use tauri_plugin_store::StoreExt;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
 use tracing::{error, warn};
 
+const AUDIO_STATS_INTERVAL: Duration = Duration::from_secs(1);
+const REALTIME_FRAME_SIZE: usize = 1024;
+const REALTIME_STOP_TIMEOUT: Duration = Duration::from_secs(6); // Covers the service's final flush
+const SECURE_STORE_FILE: &str = ".secure-settings.dat"; // Written by src/lib/secure-storage.ts
+const STT_CONFIGS_KEY: &str = "secure_stt_provider_configs";
+const ASSEMBLYAI_API_KEY: &str = "assemblyai_api_key"; // Diarization settings
+const NOISE_FLOOR_SMOOTHING: f32 = 0.05; // Per-hop weight of new silent chunks
+const NOISE_FLOOR_FALL: f32 = 0.2; // Quieter backgrounds are picked up faster
+const NOISE_FLOOR_SPEECH_CREEP: f32 = 0.002; // Lets the floor escape a step up in noise (~10s)
+const ADAPTIVE_MIN_SENSITIVITY_RMS: f32 = 0.004;
+const ADAPTIVE_MAX_SENSITIVITY_RMS: f32 = 0.1;
+const ADAPTIVE_MIN_GATE: f32 = 0.001;
+
+static NEXT_UTTERANCE_ID: AtomicU64 = AtomicU64::new(1);
+static NEXT_REALTIME_SESSION_ID: AtomicU64 = AtomicU64::new(1);
+
 // VAD Configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VadConfig {
@@ -26,6 +58,161 @@ pub struct VadConfig {
     pub pre_speech_chunks: usize,
     pub noise_gate_threshold: f32,
     pub max_recording_duration_secs: u64,
+    // Rate segments are encoded at; None keeps the capture device's native rate
+    #[serde(default = "default_target_sample_rate")]
+    pub target_sample_rate: Option<u32>,
+    // How multichannel system audio is folded to mono
+    #[serde(default)]
+    pub downmix: Downmix,
+    // Emit throttled `audio-level` events for a VU meter
+    #[serde(default)]
+    pub level_events: bool,
+    #[serde(default = "default_level_interval_ms")]
+    pub level_interval_ms: u64,
+    // Speech detector used by run_vad_capture
+    #[serde(default)]
+    pub vad_engine: VadEngine,
+    // Derive speech and gate thresholds from the measured noise floor instead of the fixed values
+    #[serde(default)]
+    pub adaptive_thresholds: bool,
+    #[serde(default = "default_adaptive_speech_ratio")]
+    pub adaptive_speech_ratio: f32, // sensitivity_rms = floor * ratio
+    #[serde(default = "default_adaptive_gate_ratio")]
+    pub adaptive_gate_ratio: f32, // noise_gate_threshold = floor * ratio
+    // STFT noise suppression on VAD segments, against noise learned between utterances
+    #[serde(default)]
+    pub noise_suppression: bool,
+    #[serde(default = "default_noise_suppression_strength")]
+    pub noise_suppression_strength: f32, // Over-subtraction; higher removes more noise and more voice
+    #[serde(default = "default_noise_suppression_floor_db")]
+    pub noise_suppression_floor_db: f32, // Maximum attenuation per frequency bin
+    // Emit rolling `speech-partial` segments while an utterance is still open
+    #[serde(default)]
+    pub partial_segments: bool,
+    #[serde(default = "default_partial_interval_ms")]
+    pub partial_interval_ms: u64, // New audio per partial
+    #[serde(default = "default_partial_overlap_ms")]
+    pub partial_overlap_ms: u64, // Audio repeated from the previous partial so words aren't cut
+    // Container for speech-detected / speech-partial audio
+    #[serde(default)]
+    pub encoding: AudioEncoding,
+    // Continuous mode without a length limit, emitted as overlapping `continuous-chunk` events
+    #[serde(default)]
+    pub chunked_continuous: bool,
+    #[serde(default = "default_continuous_chunk_secs")]
+    pub continuous_chunk_secs: u64,
+    #[serde(default = "default_continuous_chunk_overlap_ms")]
+    pub continuous_chunk_overlap_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VadEngine {
+    #[default]
+    Energy, // RMS / peak thresholds
+    Spectral, // Speech-band spectral shape over an adaptive noise floor
+}
+
+fn default_target_sample_rate() -> Option<u32> {
+    Some(16_000) // What most STT backends expect
+}
+
+fn default_level_interval_ms() -> u64 {
+    50
+}
+
+fn default_adaptive_speech_ratio() -> f32 {
+    3.0 // ~10 dB over the background
+}
+
+fn default_adaptive_gate_ratio() -> f32 {
+    1.5
+}
+
+fn default_noise_suppression_strength() -> f32 {
+    1.0
+}
+
+fn default_noise_suppression_floor_db() -> f32 {
+    -20.0
+}
+
+fn default_partial_interval_ms() -> u64 {
+    3000
+}
+
+fn default_partial_overlap_ms() -> u64 {
+    500
+}
+
+fn default_continuous_chunk_secs() -> u64 {
+    30
+}
+
+fn default_continuous_chunk_overlap_ms() -> u64 {
+    1000
+}
+
+impl VadConfig {
+    fn output_sample_rate(&self, capture_rate: u32) -> u32 {
+        self.target_sample_rate.unwrap_or(capture_rate)
+    }
+
+    // Checked by every command that accepts a config
+    pub fn validate(&self) -> Result<(), String> {
+        if self.sensitivity_rms < 0.0 || self.sensitivity_rms > 1.0 {
+            return Err("Invalid sensitivity_rms: must be 0.0-1.0".to_string());
+        }
+        if self.max_recording_duration_secs > 3600 {
+            return Err(
+                "Invalid max_recording_duration_secs: must be <= 3600 (1 hour)".to_string(),
+            );
+        }
+        if let Some(rate) = self.target_sample_rate {
+            if !(8000..=96000).contains(&rate) {
+                return Err("Invalid target_sample_rate: must be 8000-96000 Hz".to_string());
+            }
+        }
+        if !(10..=1000).contains(&self.level_interval_ms) {
+            return Err("Invalid level_interval_ms: must be 10-1000".to_string());
+        }
+        if !(1.0..=20.0).contains(&self.adaptive_speech_ratio)
+            || !(1.0..=20.0).contains(&self.adaptive_gate_ratio)
+        {
+            return Err("Invalid adaptive ratio: must be 1.0-20.0".to_string());
+        }
+        if !(0.5..=4.0).contains(&self.noise_suppression_strength) {
+            return Err("Invalid noise_suppression_strength: must be 0.5-4.0".to_string());
+        }
+        if !(-40.0..=0.0).contains(&self.noise_suppression_floor_db) {
+            return Err("Invalid noise_suppression_floor_db: must be -40-0 dB".to_string());
+        }
+        if !(500..=30_000).contains(&self.partial_interval_ms) {
+            return Err("Invalid partial_interval_ms: must be 500-30000".to_string());
+        }
+        if self.partial_overlap_ms >= self.partial_interval_ms {
+            return Err(
+                "Invalid partial_overlap_ms: must be shorter than partial_interval_ms".to_string(),
+            );
+        }
+        if !(5..=600).contains(&self.continuous_chunk_secs) {
+            return Err("Invalid continuous_chunk_secs: must be 5-600".to_string());
+        }
+        if self.continuous_chunk_overlap_ms >= self.continuous_chunk_secs * 1000 {
+            return Err(
+                "Invalid continuous_chunk_overlap_ms: must be shorter than continuous_chunk_secs"
+                    .to_string(),
+            );
+        }
+        if !self.encoding.is_available() {
+            return Err(format!(
+                "Invalid encoding: {:?} is not available in this build",
+                self.encoding
+            ));
+        }
+
+        Ok(())
+    }
 }
 
 impl Default for VadConfig {
@@ -40,13 +227,72 @@ impl Default for VadConfig {
             pre_speech_chunks: 12,  // ~0.27s - enough to catch word start
             noise_gate_threshold: 0.003, // Stronger noise filtering
             max_recording_duration_secs: 180, // 3 minutes default
+            target_sample_rate: default_target_sample_rate(),
+            downmix: Downmix::default(),
+            level_events: false,
+            level_interval_ms: default_level_interval_ms(),
+            vad_engine: VadEngine::default(),
+            adaptive_thresholds: false,
+            adaptive_speech_ratio: default_adaptive_speech_ratio(),
+            adaptive_gate_ratio: default_adaptive_gate_ratio(),
+            noise_suppression: false,
+            noise_suppression_strength: default_noise_suppression_strength(),
+            noise_suppression_floor_db: default_noise_suppression_floor_db(),
+            partial_segments: false,
+            partial_interval_ms: default_partial_interval_ms(),
+            partial_overlap_ms: default_partial_overlap_ms(),
+            encoding: AudioEncoding::default(),
+            chunked_continuous: false,
+            continuous_chunk_secs: default_continuous_chunk_secs(),
+            continuous_chunk_overlap_ms: default_continuous_chunk_overlap_ms(),
         }
     }
 }
 
+// Which channel a speech segment was captured from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioSource {
+    Mic,
+    System,
+}
+
+// `speech-detected` payload for every capture, tagged with the channel it came from
+#[derive(Debug, Clone, Serialize)]
+pub struct SpeechSegment {
+    pub source: AudioSource,
+    pub start_ms: u64, // Unix epoch milliseconds of the first sample
+    #[serde(flatten)]
+    pub segment: SegmentInfo, // Audio is fetched with get_audio_segment
+}
+
+// `speech-partial` payload. Partials of one utterance share its ID; the is_final
+// segment covers the audio after the last partial and closes the utterance.
+#[derive(Debug, Clone, Serialize)]
+pub struct PartialSegment {
+    pub utterance_id: u64,
+    pub sequence: u32,
+    pub is_final: bool,
+    pub source: Option<AudioSource>,
+    pub start_ms: u64, // Unix epoch milliseconds of the first sample
+    #[serde(flatten)]
+    pub segment: SegmentInfo, // Audio is fetched with get_audio_segment
+}
+
+// `continuous-chunk` payload; the is_final chunk ends the recording
+#[derive(Debug, Clone, Serialize)]
+pub struct ContinuousChunk {
+    pub sequence: u32,
+    pub is_final: bool,
+    pub offset_ms: u64, // Position in the recording (recorded time, excluding pauses)
+    pub overlap_ms: u64, // Leading audio repeated from the previous chunk
+    #[serde(flatten)]
+    pub segment: SegmentInfo,
+}
+
 #[tauri::command]
-pub async fn start_system_audio_capture(
-    app: AppHandle,
+pub async fn start_system_audio_capture<R: Runtime>(
+    app: AppHandle<R>,
     vad_config: Option<VadConfig>,
     device_id: Option<String>,
 ) -> Result<(), String> {
@@ -67,6 +313,7 @@ pub async fn start_system_audio_capture(
 
     // Update VAD config if provided
     if let Some(config) = vad_config {
+        config.validate()?;
         let mut vad_cfg = state
             .vad_config
             .lock()
@@ -74,10 +321,19 @@ pub async fn start_system_audio_capture(
         *vad_cfg = config;
     }
 
-    let input = SpeakerInput::new_with_device(device_id).map_err(|e| {
-        error!("Failed to create speaker input: {}", e);
-        format!("Failed to access system audio: {}", e)
-    })?;
+    let vad_config = state
+        .vad_config
+        .lock()
+        .map_err(|e| format!("Failed to read VAD config: {}", e))?
+        .clone();
+
+    let input = SpeakerInput::new_with_device(device_id)
+        .map_err(|e| {
+            error!("Failed to create speaker input: {}", e);
+            format!("Failed to access system audio: {}", e)
+        })?
+        .on_device_event(device_event_emitter(&app))
+        .with_channel_mode(ChannelMode::Mono(vad_config.downmix));
 
     let stream = input.stream();
     let sr = stream.sample_rate();
@@ -91,12 +347,12 @@ pub async fn start_system_audio_capture(
         ));
     }
 
+    let monitor = watch_capture(
+        &app,
+        CaptureMonitor::new(AudioSource::System, stream.backend(), sr, stream.queue()),
+    );
+    let frames = stream.frames(vad_config.hop_size);
     let app_clone = app.clone();
-    let vad_config = state
-        .vad_config
-        .lock()
-        .map_err(|e| format!("Failed to read VAD config: {}", e))?
-        .clone();
 
     // Mark as capturing BEFORE spawning task
     *state
@@ -107,12 +363,16 @@ pub async fn start_system_audio_capture(
     // Emit capture started event
     let _ = app_clone.emit("capture-started", sr);
 
-    let state_clone = app.state::<crate::AudioState>();
-    let task = tokio::spawn(async move {
+    // Stored under the lock so a stream that ends at once can't clear the slot first
+    let mut task_slot = state
+        .stream_task
+        .lock()
+        .map_err(|e| format!("Failed to store task: {}", e))?;
+    *task_slot = Some(tokio::spawn(async move {
         if vad_config.enabled {
-            run_vad_capture(app_clone.clone(), stream, sr, vad_config).await;
+            run_vad_capture(app_clone.clone(), frames, sr, vad_config, None, monitor).await;
         } else {
-            run_continuous_capture(app_clone.clone(), stream, sr, vad_config).await;
+            run_continuous_capture(app_clone.clone(), frames, sr, vad_config, monitor).await;
         }
 
         let state = app_clone.state::<crate::AudioState>();
@@ -121,155 +381,540 @@ pub async fn start_system_audio_capture(
                 *guard = None;
             };
         }
+    }));
+
+    Ok(())
+}
+
+// Forwards hot-plug notifications from the capture thread to the frontend
+fn device_event_emitter<R: Runtime>(
+    app: &AppHandle<R>,
+) -> impl Fn(DeviceEvent) + Send + Sync + 'static {
+    let app = app.clone();
+    move |event| match event {
+        DeviceEvent::Changed { device } => {
+            warn!("Audio capture switched to device: {}", device);
+            let _ = app.emit("audio-device-changed", device);
+        }
+        DeviceEvent::Lost { device, error } => {
+            warn!("Audio capture lost device {}: {}", device, error);
+            let _ = app.emit(
+                "audio-device-lost",
+                json!({ "device": device, "error": error }),
+            );
+        }
+    }
+}
+
+// Registers a capture for get_audio_diagnostics and emits `audio-stats` until it stops
+fn watch_capture<R: Runtime>(app: &AppHandle<R>, monitor: CaptureMonitor) -> Arc<CaptureMonitor> {
+    let monitor = Arc::new(monitor);
+
+    let state = app.state::<crate::AudioState>();
+    if let Ok(mut monitors) = state.capture_monitors.lock() {
+        monitors.insert(monitor.source(), monitor.clone());
+    }
+
+    let app = app.clone();
+    let watched = monitor.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(AUDIO_STATS_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            // One last snapshot after the stream closes
+            let running = watched.is_running();
+            let _ = app.emit("audio-stats", watched.snapshot());
+            if !running {
+                break;
+            }
+        }
     });
 
-    *state_clone
-        .stream_task
-        .lock()
-        .map_err(|e| format!("Failed to store task: {}", e))? = Some(task);
+    monitor
+}
 
-    Ok(())
+// Spectral detector tuning
+const SPEECH_BAND_HZ: (f32, f32) = (300.0, 3400.0);
+const ANALYSIS_LOW_HZ: f32 = 80.0; // Below this is mostly rumble / mains hum
+const MIN_SPEECH_RMS: f32 = 0.002; // Absolute floor so near-silence never counts
+const SPEECH_SNR: f32 = 2.0; // Required level over the noise floor (~6 dB)
+const MIN_BAND_RATIO: f32 = 0.5; // Share of energy inside the speech band
+const MAX_FLATNESS: f32 = 0.35; // Noise and clicks are flat, voiced speech is not
+const MAX_TONALITY: f32 = 0.7; // Share of band energy in the 3 strongest bins (beeps)
+
+// Decides whether one hop of noise-gated audio contains speech
+pub(super) trait VoiceDetector: Send {
+    fn is_speech(&mut self, chunk: &[f32], rms: f32, peak: f32) -> bool;
+
+    // Called every hop with the current (possibly adaptive) thresholds
+    fn set_thresholds(&mut self, _sensitivity_rms: f32, _peak_threshold: f32) {}
+}
+
+pub(super) fn build_detector(config: &VadConfig, sample_rate: u32) -> Box<dyn VoiceDetector> {
+    match config.vad_engine {
+        VadEngine::Energy => Box::new(EnergyDetector {
+            sensitivity_rms: config.sensitivity_rms,
+            peak_threshold: config.peak_threshold,
+        }),
+        VadEngine::Spectral => Box::new(SpectralDetector::new(sample_rate, config.hop_size)),
+    }
+}
+
+// Original RMS / peak gate
+struct EnergyDetector {
+    sensitivity_rms: f32,
+    peak_threshold: f32,
+}
+
+impl VoiceDetector for EnergyDetector {
+    fn is_speech(&mut self, _chunk: &[f32], rms: f32, peak: f32) -> bool {
+        rms > self.sensitivity_rms || peak > self.peak_threshold
+    }
+
+    fn set_thresholds(&mut self, sensitivity_rms: f32, peak_threshold: f32) {
+        self.sensitivity_rms = sensitivity_rms;
+        self.peak_threshold = peak_threshold;
+    }
+}
+
+// Background level of the raw (pre-gate) input
+pub(super) struct NoiseFloor {
+    level: Option<f32>,
+}
+
+impl NoiseFloor {
+    pub(super) fn new() -> Self {
+        Self { level: None }
+    }
+
+    pub(super) fn level(&self) -> f32 {
+        self.level.unwrap_or(0.0)
+    }
+
+    pub(super) fn update(&mut self, rms: f32, is_speech: bool) {
+        let Some(level) = self.level else {
+            self.level = Some(rms);
+            return;
+        };
+
+        let rate = if is_speech {
+            // Only creeps up, so a fan switching on can't pin the VAD in speech
+            if rms <= level {
+                return;
+            }
+            NOISE_FLOOR_SPEECH_CREEP
+        } else if rms < level {
+            NOISE_FLOOR_FALL
+        } else {
+            NOISE_FLOOR_SMOOTHING
+        };
+        self.level = Some(level + (rms - level) * rate);
+    }
+
+    pub(super) fn thresholds(&self, config: &VadConfig) -> NoiseEstimate {
+        let noise_floor = self.level();
+        if !config.adaptive_thresholds || self.level.is_none() {
+            return NoiseEstimate {
+                noise_floor,
+                sensitivity_rms: config.sensitivity_rms,
+                peak_threshold: config.peak_threshold,
+                noise_gate_threshold: config.noise_gate_threshold,
+                adaptive: false,
+            };
+        }
+
+        let sensitivity_rms = (noise_floor * config.adaptive_speech_ratio)
+            .clamp(ADAPTIVE_MIN_SENSITIVITY_RMS, ADAPTIVE_MAX_SENSITIVITY_RMS);
+        // Keep the configured peak / RMS relationship
+        let peak_ratio = config.peak_threshold / config.sensitivity_rms.max(f32::EPSILON);
+
+        NoiseEstimate {
+            noise_floor,
+            sensitivity_rms,
+            peak_threshold: (sensitivity_rms * peak_ratio).min(1.0),
+            noise_gate_threshold: (noise_floor * config.adaptive_gate_ratio)
+                .clamp(ADAPTIVE_MIN_GATE, sensitivity_rms),
+            adaptive: true,
+        }
+    }
+}
+
+// Rejects broadband noise (keyboard, fans) and pure tones (notifications), and accepts
+// quiet voices that clear the noise floor without reaching the fixed RMS threshold
+struct SpectralDetector {
+    spectrum: PowerSpectrum,
+    speech_bins: (usize, usize),
+    analysis_low_bin: usize,
+    noise_floor: Option<f32>,
+}
+
+impl SpectralDetector {
+    fn new(sample_rate: u32, hop_size: usize) -> Self {
+        let spectrum = PowerSpectrum::new(hop_size);
+        let bin_hz = spectrum.bin_hz(sample_rate);
+        let nyquist_bin = hop_size.next_power_of_two().max(2) / 2;
+        let bin = |hz: f32| ((hz / bin_hz).round() as usize).clamp(1, nyquist_bin);
+
+        Self {
+            speech_bins: (bin(SPEECH_BAND_HZ.0), bin(SPEECH_BAND_HZ.1)),
+            analysis_low_bin: bin(ANALYSIS_LOW_HZ),
+            spectrum,
+            noise_floor: None,
+        }
+    }
+
+    fn update_floor(&mut self, rms: f32) {
+        self.noise_floor = Some(match self.noise_floor {
+            Some(floor) => floor + (rms - floor) * NOISE_FLOOR_SMOOTHING,
+            None => rms,
+        });
+    }
+}
+
+impl VoiceDetector for SpectralDetector {
+    fn is_speech(&mut self, chunk: &[f32], rms: f32, _peak: f32) -> bool {
+        let loud_enough = rms > MIN_SPEECH_RMS
+            && self
+                .noise_floor
+                .map_or(true, |floor| rms > floor * SPEECH_SNR);
+        if !loud_enough {
+            self.update_floor(rms);
+            return false;
+        }
+
+        let (lo, hi) = self.speech_bins;
+        let power = self.spectrum.compute(chunk);
+        let band = &power[lo..=hi];
+
+        let total = power[self.analysis_low_bin..].iter().sum::<f32>() + f32::EPSILON;
+        let band_energy = band.iter().sum::<f32>() + f32::EPSILON;
+
+        // Geometric over arithmetic mean: ~0.56 for white noise, near 0 for voiced speech
+        let log_mean = band.iter().map(|&p| (p + 1e-12).ln()).sum::<f32>() / band.len() as f32;
+        let flatness = log_mean.exp() / (band_energy / band.len() as f32);
+
+        let mut strongest = [0.0f32; 3];
+        for &p in band {
+            if p > strongest[2] {
+                strongest[2] = p;
+                strongest.sort_by(|a, b| b.total_cmp(a));
+            }
+        }
+        let tonality = strongest.iter().sum::<f32>() / band_energy;
+
+        let speech = band_energy / total > MIN_BAND_RATIO
+            && flatness < MAX_FLATNESS
+            && tonality < MAX_TONALITY;
+        if !speech {
+            self.update_floor(rms);
+        }
+        speech
+    }
 }
 
 // VAD-enabled capture - OPTIMIZED for real-time speech detection
-async fn run_vad_capture(
-    app: AppHandle,
-    stream: impl StreamExt<Item = f32> + Unpin,
+// Segments are stored and emitted as SegmentInfo (an ID, not the audio); `source` tags
+// each one and defaults to System for single-source captures
+async fn run_vad_capture<R: Runtime>(
+    app: AppHandle<R>,
+    frames: impl Stream<Item = AudioFrame> + Unpin,
     sr: u32,
     config: VadConfig,
+    source: Option<AudioSource>,
+    monitor: Arc<CaptureMonitor>,
 ) {
-    let mut stream = stream;
-    let mut buffer: VecDeque<f32> = VecDeque::new();
-    let mut pre_speech: VecDeque<f32> =
-        VecDeque::with_capacity(config.pre_speech_chunks * config.hop_size);
-    let mut speech_buffer = Vec::new();
-    let mut in_speech = false;
-    let mut silence_chunks = 0;
-    let mut speech_chunks = 0;
-    let max_samples = sr as usize * 30; // 30s safety cap per utterance
-
-    while let Some(sample) = stream.next().await {
-        buffer.push_back(sample);
-
-        // Process in fixed chunks for VAD analysis
-        while buffer.len() >= config.hop_size {
-            let mut mono = Vec::with_capacity(config.hop_size);
-            for _ in 0..config.hop_size {
-                if let Some(v) = buffer.pop_front() {
-                    mono.push(v);
+    let mut frames = frames;
+    let mut speech_start_ms = 0u64;
+    let out_sr = config.output_sample_rate(sr);
+    let mut partials = config
+        .partial_segments
+        .then(|| PartialSegments::new(&config, source, sr));
+    let mut level_meter = config.level_events.then(|| {
+        LevelMeter::new(
+            source.unwrap_or(AudioSource::System),
+            config.level_interval_ms,
+        )
+    });
+    let encoding = config.encoding;
+    let mut splitter = UtteranceSplitter::new(config, sr);
+
+    // Frames are hop_size long, so each one is a VAD chunk
+    while let Some(frame) = frames.next().await {
+        monitor.record_level(&frame.samples);
+        record_meeting_audio(&app, monitor.source(), sr, &frame.samples);
+
+        let hop = splitter.push(&frame.samples);
+        monitor.record_noise(hop.thresholds);
+
+        match hop.event {
+            SplitEvent::Continue => {}
+            SplitEvent::Started { lead_in } => {
+                // Segment starts at the oldest buffered sample, not at detection
+                speech_start_ms = frame
+                    .captured_at_ms
+                    .saturating_sub(samples_to_millis(lead_in, sr));
+
+                if let Some(partials) = partials.as_mut() {
+                    partials.start();
                 }
+
+                let _ = app.emit("speech-start", source.unwrap_or(AudioSource::System));
             }
+            SplitEvent::Completed(utterance) => {
+                let normalized_buffer = splitter.clean(&utterance.samples);
+                match store_segment(&app, encoding, sr, out_sr, &normalized_buffer) {
+                    Ok(segment) => emit_speech_detected(&app, source, speech_start_ms, segment),
+                    Err(e) => {
+                        error!("Failed to encode speech segment: {}", e);
+                        let _ = app.emit("audio-encoding-error", "Failed to encode speech");
+                    }
+                }
 
-            // Apply noise gate BEFORE VAD (critical for accuracy)
-            let mono = apply_noise_gate(&mono, config.noise_gate_threshold);
+                if let Some(partials) = partials.as_mut() {
+                    partials.emit(
+                        &app,
+                        &mut splitter,
+                        Some(&utterance.samples[..]),
+                        speech_start_ms,
+                    );
+                }
+            }
+            SplitEvent::Discarded(utterance) => {
+                let _ = app.emit(
+                    "speech-discarded",
+                    "Audio too short (likely background noise)",
+                );
+
+                // Close the utterance only if the UI has already seen part of it
+                if let Some(partials) = partials.as_mut().filter(|p| p.sequence > 0) {
+                    partials.emit(
+                        &app,
+                        &mut splitter,
+                        Some(&utterance.samples[..]),
+                        speech_start_ms,
+                    );
+                }
+            }
+        }
 
-            let (rms, peak) = calculate_audio_metrics(&mono);
-            let is_speech = rms > config.sensitivity_rms || peak > config.peak_threshold;
+        // Rolling partials while the utterance is still open
+        if let Some(partials) = partials.as_mut().filter(|_| splitter.is_open()) {
+            if partials.is_due(splitter.speech_len()) {
+                partials.emit(&app, &mut splitter, None, speech_start_ms);
+            }
+        }
 
-            if is_speech {
-                if !in_speech {
-                    // Speech START detected
-                    in_speech = true;
-                    speech_chunks = 0;
+        if let Some(meter) = level_meter.as_mut() {
+            meter.update(
+                &app,
+                hop.rms,
+                hop.peak,
+                splitter.noise_floor(),
+                splitter.vad_state(),
+            );
+        }
+    }
+}
 
-                    // Include pre-speech buffer for natural sound
-                    speech_buffer.extend(pre_speech.drain(..));
+// Rolling partial segments of the open utterance (VadConfig::partial_segments)
+struct PartialSegments {
+    source: Option<AudioSource>,
+    sample_rate: u32,
+    out_sample_rate: u32,
+    encoding: AudioEncoding,
+    interval: usize, // Samples of new audio per partial
+    overlap: usize,
+    utterance_id: u64,
+    sequence: u32,
+    emitted: usize, // Speech buffer samples already covered by a partial
+}
 
-                    let _ = app.emit("speech-start", ());
-                }
+impl PartialSegments {
+    fn new(config: &VadConfig, source: Option<AudioSource>, sample_rate: u32) -> Self {
+        let samples = |ms: u64| (sample_rate as u64 * ms / 1000) as usize;
+        Self {
+            source,
+            sample_rate,
+            out_sample_rate: config.output_sample_rate(sample_rate),
+            encoding: config.encoding,
+            interval: samples(config.partial_interval_ms).max(1),
+            overlap: samples(config.partial_overlap_ms),
+            utterance_id: 0,
+            sequence: 0,
+            emitted: 0,
+        }
+    }
 
-                speech_chunks += 1;
-                speech_buffer.extend_from_slice(&mono);
-                silence_chunks = 0; // Reset silence counter on any speech
+    fn start(&mut self) {
+        self.utterance_id = NEXT_UTTERANCE_ID.fetch_add(1, Ordering::Relaxed);
+        self.sequence = 0;
+        self.emitted = 0;
+    }
 
-                // Safety cap: force emit if exceeds 30s
-                if speech_buffer.len() > max_samples {
-                    let normalized_buffer = normalize_audio_level(&speech_buffer, 0.1);
-                    if let Ok(b64) = samples_to_wav_b64(sr, &normalized_buffer) {
-                        // let duration = speech_buffer.len() as f32 / sr as f32;
-                        let _ = app.emit("speech-detected", b64);
-                    }
-                    speech_buffer.clear();
-                    in_speech = false;
-                    speech_chunks = 0;
-                }
-            } else {
-                // Silence detected
-                if in_speech {
-                    silence_chunks += 1;
-
-                    // Continue collecting during silence (important for natural speech)
-                    speech_buffer.extend_from_slice(&mono);
-
-                    // Check if silence duration exceeds threshold
-                    if silence_chunks >= config.silence_chunks {
-                        // Verify minimum speech duration
-                        if speech_chunks >= config.min_speech_chunks && !speech_buffer.is_empty() {
-                            // Trim trailing silence (keep ~0.15s for natural ending)
-                            let silence_duration_samples = silence_chunks * config.hop_size;
-                            let keep_silence_samples = (sr as usize) * 15 / 100; // 0.15s
-                            let trim_amount =
-                                silence_duration_samples.saturating_sub(keep_silence_samples);
-
-                            if speech_buffer.len() > trim_amount {
-                                speech_buffer.truncate(speech_buffer.len() - trim_amount);
-                            }
-
-                            // Emit complete speech segment
-                            let normalized_buffer = normalize_audio_level(&speech_buffer, 0.1);
-                            if let Ok(b64) = samples_to_wav_b64(sr, &normalized_buffer) {
-                                // let duration = speech_buffer.len() as f32 / sr as f32;
-                                let _ = app.emit("speech-detected", b64);
-                            } else {
-                                error!("Failed to encode speech to WAV");
-                                let _ = app.emit("audio-encoding-error", "Failed to encode speech");
-                            }
-                        } else {
-                            let _ = app.emit(
-                                "speech-discarded",
-                                "Audio too short (likely background noise)",
-                            );
-                        }
+    fn is_due(&self, buffered: usize) -> bool {
+        buffered.saturating_sub(self.emitted) >= self.interval
+    }
 
-                        // Reset for next speech detection
-                        speech_buffer.clear();
-                        in_speech = false;
-                        silence_chunks = 0;
-                        speech_chunks = 0;
-                    }
-                } else {
-                    // Not in speech yet - maintain rolling pre-speech buffer
-                    pre_speech.extend(mono);
+    // `finished` is the closed utterance for the final partial; None covers the open one
+    fn emit<R: Runtime>(
+        &mut self,
+        app: &AppHandle<R>,
+        splitter: &mut UtteranceSplitter,
+        finished: Option<&[f32]>,
+        speech_start_ms: u64,
+    ) {
+        let buffered = finished.map_or(splitter.speech_len(), |speech| speech.len());
+        // The final segment may start before `emitted` if trailing silence was trimmed
+        let from = self.emitted.min(buffered).saturating_sub(self.overlap);
+        let audio = match finished {
+            Some(speech) => splitter.clean(&speech[from..]),
+            None => splitter.clean_speech(from),
+        };
+
+        match store_segment(
+            app,
+            self.encoding,
+            self.sample_rate,
+            self.out_sample_rate,
+            &audio,
+        ) {
+            Ok(segment) => {
+                let _ = app.emit(
+                    "speech-partial",
+                    PartialSegment {
+                        utterance_id: self.utterance_id,
+                        sequence: self.sequence,
+                        is_final: finished.is_some(),
+                        source: self.source,
+                        start_ms: speech_start_ms + samples_to_millis(from, self.sample_rate),
+                        segment,
+                    },
+                );
+            }
+            Err(e) => error!("Failed to encode partial segment: {}", e),
+        }
 
-                    // Trim excess (maintain fixed size)
-                    while pre_speech.len() > config.pre_speech_chunks * config.hop_size {
-                        pre_speech.pop_front();
-                    }
+        self.sequence += 1;
+        self.emitted = buffered;
+    }
+}
 
-                    // Periodically shrink capacity to prevent memory bloat
-                    if pre_speech.len() == config.pre_speech_chunks * config.hop_size {
-                        pre_speech.shrink_to_fit();
-                    }
-                }
-            }
+// Where the VAD is within an utterance
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(super) enum VadState {
+    Silence,
+    Speech,
+    Hangover, // In speech, counting silent chunks before the segment ends
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct AudioLevel {
+    source: AudioSource,
+    rms: f32,
+    peak: f32,
+    noise_floor: f32,
+    vad_state: VadState,
+}
+
+// Throttles per-hop metrics into `audio-level` events (RMS over the interval, max peak)
+struct LevelMeter {
+    source: AudioSource,
+    interval: Duration,
+    last_emit: Instant,
+    sum_squares: f32,
+    hops: usize,
+    peak: f32,
+}
+
+impl LevelMeter {
+    fn new(source: AudioSource, interval_ms: u64) -> Self {
+        Self {
+            source,
+            interval: Duration::from_millis(interval_ms),
+            last_emit: Instant::now(),
+            sum_squares: 0.0,
+            hops: 0,
+            peak: 0.0,
         }
     }
+
+    fn update<R: Runtime>(
+        &mut self,
+        app: &AppHandle<R>,
+        rms: f32,
+        peak: f32,
+        noise_floor: f32,
+        state: VadState,
+    ) {
+        self.sum_squares += rms * rms;
+        self.hops += 1;
+        self.peak = self.peak.max(peak);
+
+        if self.last_emit.elapsed() < self.interval {
+            return;
+        }
+
+        let _ = app.emit(
+            "audio-level",
+            AudioLevel {
+                source: self.source,
+                rms: (self.sum_squares / self.hops as f32).sqrt(),
+                peak: self.peak,
+                noise_floor,
+                vad_state: state,
+            },
+        );
+
+        self.last_emit = Instant::now();
+        self.sum_squares = 0.0;
+        self.hops = 0;
+        self.peak = 0.0;
+    }
+}
+
+fn emit_speech_detected<R: Runtime>(
+    app: &AppHandle<R>,
+    source: Option<AudioSource>,
+    start_ms: u64,
+    segment: SegmentInfo,
+) {
+    let _ = app.emit(
+        "speech-detected",
+        SpeechSegment {
+            source: source.unwrap_or(AudioSource::System),
+            start_ms,
+            segment,
+        },
+    );
+}
+
+pub(super) fn samples_to_millis(samples: usize, sample_rate: u32) -> u64 {
+    samples as u64 * 1000 / sample_rate.max(1) as u64
 }
 
 // Continuous capture (VAD disabled)
-async fn run_continuous_capture(
-    app: AppHandle,
-    stream: impl StreamExt<Item = f32> + Unpin,
+async fn run_continuous_capture<R: Runtime>(
+    app: AppHandle<R>,
+    frames: impl Stream<Item = AudioFrame> + Unpin,
     sr: u32,
     config: VadConfig,
+    monitor: Arc<CaptureMonitor>,
 ) {
-    let mut stream = stream;
+    let mut frames = frames;
     let max_samples = (sr as u64 * config.max_recording_duration_secs) as usize;
-
-    // Pre-allocate buffer to prevent reallocations
-    let mut audio_buffer = Vec::with_capacity(max_samples);
-    let start_time = Instant::now();
-    let max_duration = Duration::from_secs(config.max_recording_duration_secs);
+    let mut chunks = config
+        .chunked_continuous
+        .then(|| ContinuousChunks::new(&config, sr));
+
+    // Pre-allocate buffer to prevent reallocations; chunked recordings only hold one chunk
+    let mut audio_buffer = Vec::with_capacity(match &chunks {
+        Some(chunks) => chunks.capacity(),
+        None => max_samples,
+    });
+    let mut recorded = 0usize; // Samples recorded so far, across flushed chunks
+    let mut start_ms = None; // Capture time of the first recorded sample
 
     // Atomic flag for manual stop
     let stop_flag = Arc::new(AtomicBool::new(false));
@@ -280,13 +925,27 @@ async fn run_continuous_capture(
         stop_flag_for_listener.store(true, Ordering::Release);
     });
 
-    // Emit recording started
+    // Paused recordings keep the stream and buffer but drop incoming audio
+    let pause_flag = Arc::new(AtomicBool::new(false));
+    let pause_flag_for_listener = pause_flag.clone();
+    let pause_listener = app.listen("pause-continuous", move |_| {
+        pause_flag_for_listener.store(true, Ordering::Release);
+    });
+    let resume_flag_for_listener = pause_flag.clone();
+    let resume_listener = app.listen("resume-continuous", move |_| {
+        resume_flag_for_listener.store(false, Ordering::Release);
+    });
+    let mut paused = false;
+
+    // Emit recording started, with its length limit (null when chunked)
     let _ = app.emit(
         "continuous-recording-start",
-        config.max_recording_duration_secs,
+        chunks
+            .is_none()
+            .then_some(config.max_recording_duration_secs),
     );
 
-    // Accumulate audio - check stop flag on EVERY sample for immediate response
+    // Accumulate audio - check stop flag on EVERY frame for immediate response
     loop {
         // Check stop flag FIRST on every iteration for immediate stopping
         if stop_flag.load(Ordering::Acquire) {
@@ -294,30 +953,48 @@ async fn run_continuous_capture(
         }
 
         tokio::select! {
-            sample_opt = stream.next() => {
-                match sample_opt {
-                    Some(sample) => {
+            frame_opt = frames.next() => {
+                match frame_opt {
+                    Some(frame) => {
                         if stop_flag.load(Ordering::Acquire) {
                             break;
                         }
 
-                        audio_buffer.push(sample);
-
-                        let elapsed = start_time.elapsed();
+                        monitor.record_level(&frame.samples);
+                        record_meeting_audio(&app, monitor.source(), sr, &frame.samples);
 
-                        // Emit progress every second
-                        if audio_buffer.len() % (sr as usize) == 0 {
-                            let _ = app.emit("recording-progress", elapsed.as_secs());
+                        let recorded_secs = (recorded / sr as usize) as u64;
+                        if pause_flag.load(Ordering::Acquire) != paused {
+                            paused = !paused;
+                            let event = if paused {
+                                "continuous-recording-paused"
+                            } else {
+                                "continuous-recording-resumed"
+                            };
+                            let _ = app.emit(event, recorded_secs);
+                        }
+                        if paused {
+                            continue;
                         }
 
-                        // Check size limit (safety)
-                        if audio_buffer.len() >= max_samples {
-                            break;
+                        let take = match chunks {
+                            Some(_) => frame.samples.len(),
+                            None => frame.samples.len().min(max_samples - audio_buffer.len()),
+                        };
+                        start_ms.get_or_insert(frame.captured_at_ms);
+                        audio_buffer.extend_from_slice(&frame.samples[..take]);
+                        recorded += take;
+
+                        // Emit progress every second of recorded (not paused) audio
+                        if (recorded / sr as usize) as u64 > recorded_secs {
+                            let _ = app.emit("recording-progress", recorded_secs + 1);
                         }
 
-                        // Check time limit
-                        if elapsed >= max_duration {
-                            break;
+                        match chunks.as_mut() {
+                            Some(chunks) => chunks.flush_full(&app, &mut audio_buffer),
+                            // Stop at max_recording_duration_secs of recorded audio
+                            None if audio_buffer.len() >= max_samples => break,
+                            None => {}
                         }
                     },
                     None => {
@@ -331,20 +1008,38 @@ async fn run_continuous_capture(
         }
     }
 
-    // Clean up event listener (CRITICAL)
+    // Clean up event listeners (CRITICAL)
     app.unlisten(stop_listener);
+    app.unlisten(pause_listener);
+    app.unlisten(resume_listener);
 
     // Process and emit audio
-    if !audio_buffer.is_empty() {
+    if let (Some(chunks), false) = (chunks.as_mut(), audio_buffer.is_empty()) {
+        if let Err(e) = chunks.emit(&app, &audio_buffer, true) {
+            error!("Failed to encode continuous chunk: {}", e);
+            let _ = app.emit("audio-encoding-error", e);
+        }
+    } else if !audio_buffer.is_empty() {
         // let duration = start_time.elapsed().as_secs_f32();
 
         // Apply noise gate
         let cleaned_audio = apply_noise_gate(&audio_buffer, config.noise_gate_threshold);
         let cleaned_audio = normalize_audio_level(&cleaned_audio, 0.1);
 
-        match samples_to_wav_b64(sr, &cleaned_audio) {
-            Ok(b64) => {
-                let _ = app.emit("speech-detected", b64);
+        match store_segment(
+            &app,
+            config.encoding,
+            sr,
+            config.output_sample_rate(sr),
+            &cleaned_audio,
+        ) {
+            Ok(segment) => {
+                emit_speech_detected(
+                    &app,
+                    Some(monitor.source()),
+                    start_ms.unwrap_or_default(),
+                    segment,
+                );
             }
             Err(e) => {
                 error!("Failed to encode continuous audio: {}", e);
@@ -359,8 +1054,94 @@ async fn run_continuous_capture(
     let _ = app.emit("continuous-recording-stopped", ());
 }
 
+// Splits a chunked continuous recording into `continuous-chunk` events as it grows.
+// Each chunk repeats the tail of the previous one so words at the boundary aren't cut.
+struct ContinuousChunks {
+    sample_rate: u32,
+    length: usize, // New samples per chunk
+    overlap: usize,
+    noise_gate_threshold: f32,
+    encoding: AudioEncoding,
+    out_sample_rate: u32,
+    sequence: u32,
+    carried: usize, // Samples at the start of the buffer repeated from the previous chunk
+    buffer_start: usize, // Recording offset of the buffer's first sample
+}
+
+impl ContinuousChunks {
+    fn new(config: &VadConfig, sample_rate: u32) -> Self {
+        // validate() rejects these, but flushing must advance whatever the config says
+        let length = ((sample_rate as u64 * config.continuous_chunk_secs) as usize).max(1);
+        let overlap = (sample_rate as u64 * config.continuous_chunk_overlap_ms / 1000) as usize;
+
+        Self {
+            sample_rate,
+            length,
+            overlap: overlap.min(length - 1),
+            noise_gate_threshold: config.noise_gate_threshold,
+            encoding: config.encoding,
+            out_sample_rate: config.output_sample_rate(sample_rate),
+            sequence: 0,
+            carried: 0,
+            buffer_start: 0,
+        }
+    }
+
+    fn capacity(&self) -> usize {
+        // One chunk plus a second of slack for the frame that completes it
+        self.overlap + self.length + self.sample_rate as usize
+    }
+
+    // Emits every complete chunk, keeping the overlap (and anything newer) buffered.
+    // A chunk is only flushed once audio past it exists, so the final chunk is never empty.
+    fn flush_full<R: Runtime>(&mut self, app: &AppHandle<R>, buffer: &mut Vec<f32>) {
+        while buffer.len() > self.carried + self.length {
+            let end = self.carried + self.length;
+            if let Err(e) = self.emit(app, &buffer[..end], false) {
+                error!("Failed to encode continuous chunk: {}", e);
+                let _ = app.emit("audio-encoding-error", e);
+            }
+
+            let drained = end - self.overlap;
+            buffer.drain(..drained);
+            self.buffer_start += drained;
+            self.carried = self.overlap;
+        }
+    }
+
+    fn emit<R: Runtime>(
+        &mut self,
+        app: &AppHandle<R>,
+        audio: &[f32],
+        is_final: bool,
+    ) -> Result<(), String> {
+        let cleaned_audio = apply_noise_gate(audio, self.noise_gate_threshold);
+        let cleaned_audio = normalize_audio_level(&cleaned_audio, 0.1);
+        let segment = store_segment(
+            app,
+            self.encoding,
+            self.sample_rate,
+            self.out_sample_rate,
+            &cleaned_audio,
+        )?;
+
+        let _ = app.emit(
+            "continuous-chunk",
+            ContinuousChunk {
+                sequence: self.sequence,
+                is_final,
+                offset_ms: samples_to_millis(self.buffer_start, self.sample_rate),
+                overlap_ms: samples_to_millis(self.carried, self.sample_rate),
+                segment,
+            },
+        );
+        self.sequence += 1;
+        Ok(())
+    }
+}
+
 // Apply noise gate
-fn apply_noise_gate(samples: &[f32], threshold: f32) -> Vec<f32> {
+pub(super) fn apply_noise_gate(samples: &[f32], threshold: f32) -> Vec<f32> {
     const KNEE_RATIO: f32 = 3.0; // Compression ratio for soft knee
 
     samples
@@ -377,7 +1158,7 @@ fn apply_noise_gate(samples: &[f32], threshold: f32) -> Vec<f32> {
 }
 
 // Calculate RMS and peak (optimized)
-fn calculate_audio_metrics(chunk: &[f32]) -> (f32, f32) {
+pub(super) fn calculate_audio_metrics(chunk: &[f32]) -> (f32, f32) {
     let mut sumsq = 0.0f32;
     let mut peak = 0.0f32;
 
@@ -391,7 +1172,39 @@ fn calculate_audio_metrics(chunk: &[f32]) -> (f32, f32) {
     (rms, peak)
 }
 
-fn normalize_audio_level(samples: &[f32], target_rms: f32) -> Vec<f32> {
+// Feeds the active meeting recording, if any (see start_meeting_recording)
+fn record_meeting_audio<R: Runtime>(
+    app: &AppHandle<R>,
+    source: AudioSource,
+    sample_rate: u32,
+    samples: &[f32],
+) {
+    let state = app.state::<crate::AudioState>();
+    let recording = match state.meeting_recording.lock() {
+        Ok(guard) => guard.clone(),
+        Err(_) => return,
+    };
+
+    if let Some(recording) = recording {
+        if let Err(e) = recording.write(source, sample_rate, samples) {
+            error!("{}", e);
+            let _ = app.emit("meeting-recording-error", e);
+        }
+    }
+}
+
+// Runs the optional suppression stage ahead of normalize_audio_level
+pub(super) fn suppress_noise<'a>(
+    suppressor: &mut Option<NoiseSuppressor>,
+    samples: &'a [f32],
+) -> Cow<'a, [f32]> {
+    match suppressor.as_mut() {
+        Some(suppressor) => Cow::Owned(suppressor.process(samples)),
+        None => Cow::Borrowed(samples),
+    }
+}
+
+pub(super) fn normalize_audio_level(samples: &[f32], target_rms: f32) -> Vec<f32> {
     if samples.is_empty() {
         return Vec::new();
     }
@@ -418,15 +1231,23 @@ fn normalize_audio_level(samples: &[f32], target_rms: f32) -> Vec<f32> {
         .collect()
 }
 
-// Convert samples to WAV base64 (with proper error handling)
-fn samples_to_wav_b64(sample_rate: u32, mono_f32: &[f32]) -> Result<String, String> {
-    // Validate sample rate
-    if !(8000..=96000).contains(&sample_rate) {
-        error!("Invalid sample rate: {}", sample_rate);
-        return Err(format!(
-            "Invalid sample rate: {}. Expected 8000-96000 Hz",
-            sample_rate
-        ));
+// Encode samples at target_rate into the segment store (with proper error handling)
+fn store_segment<R: Runtime>(
+    app: &AppHandle<R>,
+    encoding: AudioEncoding,
+    capture_rate: u32,
+    target_rate: u32,
+    mono_f32: &[f32],
+) -> Result<SegmentInfo, String> {
+    // Validate sample rates
+    for sample_rate in [capture_rate, target_rate] {
+        if !(8000..=96000).contains(&sample_rate) {
+            error!("Invalid sample rate: {}", sample_rate);
+            return Err(format!(
+                "Invalid sample rate: {}. Expected 8000-96000 Hz",
+                sample_rate
+            ));
+        }
     }
 
     // Validate buffer
@@ -434,28 +1255,15 @@ fn samples_to_wav_b64(sample_rate: u32, mono_f32: &[f32]) -> Result<String, Stri
         return Err("Empty audio buffer".to_string());
     }
 
-    let mut cursor = Cursor::new(Vec::new());
-    let spec = WavSpec {
-        channels: 1,
-        sample_rate,
-        bits_per_sample: 16,
-        sample_format: hound::SampleFormat::Int,
-    };
-
-    let mut writer = WavWriter::new(&mut cursor, spec).map_err(|e| {
-        error!("Failed to create WAV writer: {}", e);
-        e.to_string()
-    })?;
-
-    for &s in mono_f32 {
-        let clamped = s.clamp(-1.0, 1.0);
-        let sample_i16 = (clamped * i16::MAX as f32) as i16;
-        writer.write_sample(sample_i16).map_err(|e| e.to_string())?;
-    }
-
-    writer.finalize().map_err(|e| e.to_string())?;
+    let resampled = resample(mono_f32, capture_rate, target_rate);
+    let bytes = encode::encode(encoding, target_rate, &resampled)?;
 
-    Ok(B64.encode(cursor.into_inner()))
+    let state = app.state::<crate::AudioState>();
+    Ok(state.segments.insert(
+        encoding,
+        bytes,
+        samples_to_millis(resampled.len(), target_rate),
+    ))
 }
 
 #[tauri::command]
@@ -491,16 +1299,589 @@ pub async fn stop_system_audio_capture(app: AppHandle) -> Result<(), String> {
     Ok(())
 }
 
-/// Manual stop for continuous recording
 #[tauri::command]
-pub async fn manual_stop_continuous(app: AppHandle) -> Result<(), String> {
-    let _ = app.emit("manual-stop-continuous", ());
+pub async fn start_mic_capture(
+    app: AppHandle,
+    vad_config: Option<VadConfig>,
+    device_id: Option<String>,
+) -> Result<(), String> {
+    let state = app.state::<crate::AudioState>();
+
+    // Check if already capturing (atomic check)
+    {
+        let guard = state
+            .mic_task
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+
+        if guard.is_some() {
+            warn!("Microphone capture already running");
+            return Err("Microphone capture already running".to_string());
+        }
+    }
+
+    // Explicit config applies to this capture only; the shared config stays with system audio
+    let vad_config = match vad_config {
+        Some(config) => {
+            config.validate()?;
+            config
+        }
+        None => state
+            .vad_config
+            .lock()
+            .map_err(|e| format!("Failed to read VAD config: {}", e))?
+            .clone(),
+    };
+
+    let input = MicInput::new(device_id).map_err(|e| {
+        error!("Failed to create microphone input: {}", e);
+        format!("Failed to access microphone: {}", e)
+    })?;
+
+    let stream = input.stream();
+    let sr = stream.sample_rate();
+
+    // Validate sample rate
+    if !(8000..=96000).contains(&sr) {
+        error!("Invalid microphone sample rate: {}", sr);
+        return Err(format!(
+            "Invalid sample rate: {}. Expected 8000-96000 Hz",
+            sr
+        ));
+    }
+
+    let monitor = watch_capture(
+        &app,
+        CaptureMonitor::new(
+            AudioSource::Mic,
+            stream.backend(),
+            sr,
+            stream.queue().clone(),
+        ),
+    );
+    let frames = stream.frames(vad_config.hop_size);
+    let app_clone = app.clone();
+    let _ = app.emit("mic-capture-started", sr);
+
+    // Stored under the lock so a stream that ends at once can't clear the slot first
+    let mut task_slot = state
+        .mic_task
+        .lock()
+        .map_err(|e| format!("Failed to store task: {}", e))?;
+    *task_slot = Some(tokio::spawn(async move {
+        if vad_config.enabled {
+            run_vad_capture(
+                app_clone.clone(),
+                frames,
+                sr,
+                vad_config,
+                Some(AudioSource::Mic),
+                monitor,
+            )
+            .await;
+        } else {
+            run_continuous_capture(app_clone.clone(), frames, sr, vad_config, monitor).await;
+        }
+
+        let state = app_clone.state::<crate::AudioState>();
+        {
+            if let Ok(mut guard) = state.mic_task.lock() {
+                *guard = None;
+            };
+        }
+    }));
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn stop_mic_capture(app: AppHandle) -> Result<(), String> {
+    let state = app.state::<crate::AudioState>();
+
+    {
+        let mut guard = state
+            .mic_task
+            .lock()
+            .map_err(|e| format!("Failed to acquire task lock: {}", e))?;
+
+        if let Some(task) = guard.take() {
+            task.abort();
+        }
+    }
+
+    // Give the capture thread time to release the device (mic indicator)
+    tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
+
+    let _ = app.emit("mic-capture-stopped", ());
+    Ok(())
+}
+
+// Captures system audio ("them") and the microphone ("me") side by side.
+// Each channel runs its own VAD and emits source-tagged `speech-detected` segments.
+#[tauri::command]
+pub async fn start_dual_capture(
+    app: AppHandle,
+    vad_config: Option<VadConfig>,
+    device_id: Option<String>,
+    mic_device_id: Option<String>,
+) -> Result<(), String> {
+    let state = app.state::<crate::AudioState>();
+
+    // Both channel slots must be free
+    {
+        let system_guard = state
+            .stream_task
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+        let mic_guard = state
+            .mic_task
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+
+        if system_guard.is_some() || mic_guard.is_some() {
+            warn!("Capture already running");
+            return Err("Capture already running".to_string());
+        }
+    }
+
+    // Update VAD config if provided
+    if let Some(config) = vad_config {
+        config.validate()?;
+        let mut vad_cfg = state
+            .vad_config
+            .lock()
+            .map_err(|e| format!("Failed to acquire VAD config lock: {}", e))?;
+        *vad_cfg = config;
+    }
+
+    // Per-channel tagging only makes sense with VAD, so it is always on here
+    let vad_config = state
+        .vad_config
+        .lock()
+        .map_err(|e| format!("Failed to read VAD config: {}", e))?
+        .clone();
+
+    let system_input = SpeakerInput::new_with_device(device_id)
+        .map_err(|e| {
+            error!("Failed to create speaker input: {}", e);
+            format!("Failed to access system audio: {}", e)
+        })?
+        .on_device_event(device_event_emitter(&app))
+        .with_channel_mode(ChannelMode::Mono(vad_config.downmix));
+    let mic_input = MicInput::new(mic_device_id).map_err(|e| {
+        error!("Failed to create microphone input: {}", e);
+        format!("Failed to access microphone: {}", e)
+    })?;
+
+    let system_stream = system_input.stream();
+    let system_sr = system_stream.sample_rate();
+    let mic_stream = mic_input.stream();
+    let mic_sr = mic_stream.sample_rate();
+
+    // Validate sample rates
+    for sr in [system_sr, mic_sr] {
+        if !(8000..=96000).contains(&sr) {
+            error!("Invalid sample rate: {}", sr);
+            return Err(format!(
+                "Invalid sample rate: {}. Expected 8000-96000 Hz",
+                sr
+            ));
+        }
+    }
+
+    *state
+        .is_capturing
+        .lock()
+        .map_err(|e| format!("Failed to set capturing state: {}", e))? = true;
+
+    let system_monitor = watch_capture(
+        &app,
+        CaptureMonitor::new(
+            AudioSource::System,
+            system_stream.backend(),
+            system_sr,
+            system_stream.queue(),
+        ),
+    );
+    let mic_monitor = watch_capture(
+        &app,
+        CaptureMonitor::new(
+            AudioSource::Mic,
+            mic_stream.backend(),
+            mic_sr,
+            mic_stream.queue().clone(),
+        ),
+    );
+    let system_frames = system_stream.frames(vad_config.hop_size);
+    let mic_frames = mic_stream.frames(vad_config.hop_size);
+
+    let _ = app.emit("capture-started", system_sr);
+    let _ = app.emit("mic-capture-started", mic_sr);
+
+    // Both slots stay locked until the handles are stored, so a channel that ends at
+    // once can't clear its slot first
+    let mut system_slot = state
+        .stream_task
+        .lock()
+        .map_err(|e| format!("Failed to store task: {}", e))?;
+    let mut mic_slot = state
+        .mic_task
+        .lock()
+        .map_err(|e| format!("Failed to store task: {}", e))?;
+
+    let system_app = app.clone();
+    let system_config = vad_config.clone();
+    *system_slot = Some(tokio::spawn(async move {
+        run_vad_capture(
+            system_app.clone(),
+            system_frames,
+            system_sr,
+            system_config,
+            Some(AudioSource::System),
+            system_monitor,
+        )
+        .await;
+
+        let state = system_app.state::<crate::AudioState>();
+        if let Ok(mut guard) = state.stream_task.lock() {
+            *guard = None;
+        };
+    }));
+
+    let mic_app = app.clone();
+    *mic_slot = Some(tokio::spawn(async move {
+        run_vad_capture(
+            mic_app.clone(),
+            mic_frames,
+            mic_sr,
+            vad_config,
+            Some(AudioSource::Mic),
+            mic_monitor,
+        )
+        .await;
+
+        let state = mic_app.state::<crate::AudioState>();
+        if let Ok(mut guard) = state.mic_task.lock() {
+            *guard = None;
+        };
+    }));
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn stop_dual_capture(app: AppHandle) -> Result<(), String> {
+    stop_mic_capture(app.clone()).await?;
+    stop_system_audio_capture(app).await
+}
+
+// Running realtime transcription (see start_realtime_transcription)
+pub struct RealtimeSession {
+    id: u64,
+    task: JoinHandle<()>,
+    stop: oneshot::Sender<()>,
+}
+
+// Streams system audio (or the microphone) to a WebSocket STT service and emits
+// `transcript-partial` / `transcript-final` while people are still talking
+#[tauri::command]
+pub async fn start_realtime_transcription(
+    app: AppHandle,
+    mut config: RealtimeSttConfig,
+    source: Option<AudioSource>,
+    device_id: Option<String>,
+) -> Result<(), String> {
+    let state = app.state::<crate::AudioState>();
+
+    // Held until the session is stored, so a task that ends at once still finds its entry
+    let mut guard = state
+        .realtime_session
+        .lock()
+        .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+
+    if guard.is_some() {
+        warn!("Realtime transcription already running");
+        return Err("Realtime transcription already running".to_string());
+    }
+
+    config.api_key = stored_realtime_api_key(&app, config.provider)?;
+    let downmix = state
+        .vad_config
+        .lock()
+        .map_err(|e| format!("Failed to read VAD config: {}", e))?
+        .downmix;
+
+    let source = source.unwrap_or(AudioSource::System);
+    let session = match source {
+        AudioSource::System => {
+            let stream = SpeakerInput::new_with_device(device_id)
+                .map_err(|e| {
+                    error!("Failed to create speaker input: {}", e);
+                    format!("Failed to access system audio: {}", e)
+                })?
+                .on_device_event(device_event_emitter(&app))
+                .with_channel_mode(ChannelMode::Mono(downmix))
+                .stream();
+            let sr = stream.sample_rate();
+            spawn_realtime_transcription(
+                &app,
+                config,
+                source,
+                stream.frames(REALTIME_FRAME_SIZE),
+                sr,
+            )
+        }
+        AudioSource::Mic => {
+            let stream = MicInput::new(device_id)
+                .map_err(|e| {
+                    error!("Failed to create microphone input: {}", e);
+                    format!("Failed to access microphone: {}", e)
+                })?
+                .stream();
+            let sr = stream.sample_rate();
+            spawn_realtime_transcription(
+                &app,
+                config,
+                source,
+                stream.frames(REALTIME_FRAME_SIZE),
+                sr,
+            )
+        }
+    };
+
+    *guard = Some(session);
+    Ok(())
+}
+
+// The provider's API key as saved in the STT settings, read here so it never crosses IPC
+fn stored_realtime_api_key(app: &AppHandle, provider: RealtimeProvider) -> Result<String, String> {
+    let store = app
+        .store(SECURE_STORE_FILE)
+        .map_err(|e| format!("Failed to open saved settings: {}", e))?;
+    let saved = |key: &str| {
+        store
+            .get(key)
+            .and_then(|value| value.as_str().map(str::to_string))
+    };
+
+    // Per-provider variables, stored as one JSON string (see secure-provider-configs.ts)
+    let from_stt_config = saved(STT_CONFIGS_KEY)
+        .and_then(|json| {
+            serde_json::from_str::<HashMap<String, HashMap<String, String>>>(&json).ok()
+        })
+        .and_then(|mut configs| configs.remove(provider.stt_provider_id()))
+        .and_then(|mut variables| {
+            variables
+                .remove("api_key")
+                .or_else(|| variables.remove("API_KEY"))
+        });
+    let api_key = match provider {
+        RealtimeProvider::AssemblyAi => from_stt_config.or_else(|| saved(ASSEMBLYAI_API_KEY)),
+        _ => from_stt_config,
+    };
+
+    api_key
+        .filter(|key| !key.trim().is_empty())
+        .ok_or_else(|| format!("No API key saved for {:?} transcription", provider))
+}
+
+fn spawn_realtime_transcription(
+    app: &AppHandle,
+    config: RealtimeSttConfig,
+    source: AudioSource,
+    frames: impl Stream<Item = AudioFrame> + Unpin + Send + 'static,
+    sample_rate: u32,
+) -> RealtimeSession {
+    let (stop, stop_rx) = oneshot::channel();
+    let app = app.clone();
+    let id = NEXT_REALTIME_SESSION_ID.fetch_add(1, Ordering::Relaxed);
+
+    let task = tokio::spawn(async move {
+        let result = stream_transcription(&config, frames, sample_rate, stop_rx, |transcript| {
+            let event = if transcript.is_final {
+                "transcript-final"
+            } else {
+                "transcript-partial"
+            };
+            let _ = app.emit(event, json!({ "source": source, "text": transcript.text }));
+        })
+        .await;
+
+        if let Err(e) = result {
+            error!("Realtime transcription failed: {}", e);
+            let _ = app.emit("transcript-error", e);
+        }
+
+        // Only clear our own entry; a newer session may have been started since
+        let state = app.state::<crate::AudioState>();
+        if let Ok(mut guard) = state.realtime_session.lock() {
+            if guard.as_ref().is_some_and(|session| session.id == id) {
+                *guard = None;
+            }
+        };
+        let _ = app.emit("realtime-transcription-stopped", source);
+    });
+
+    RealtimeSession { id, task, stop }
+}
+
+#[tauri::command]
+pub async fn stop_realtime_transcription(app: AppHandle) -> Result<(), String> {
+    let session = app
+        .state::<crate::AudioState>()
+        .realtime_session
+        .lock()
+        .map_err(|e| format!("Failed to acquire task lock: {}", e))?
+        .take();
+
+    if let Some(RealtimeSession { mut task, stop, .. }) = session {
+        // Let the service flush its last finals before giving up on it
+        let _ = stop.send(());
+        if tokio::time::timeout(REALTIME_STOP_TIMEOUT, &mut task)
+            .await
+            .is_err()
+        {
+            task.abort();
+        }
+    }
+
+    Ok(())
+}
+
+fn recordings_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data directory: {}", e))?
+        .join("recordings"))
+}
+
+// Records every running capture (system and/or mic, one track each) to app data until
+// stop_meeting_recording. VAD keeps running on the same frames.
+#[tauri::command]
+pub async fn start_meeting_recording(
+    app: AppHandle,
+    conversation_id: String,
+) -> Result<MeetingRecordingInfo, String> {
+    let state = app.state::<crate::AudioState>();
+    let mut guard = state
+        .meeting_recording
+        .lock()
+        .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+
+    if guard.is_some() {
+        warn!("Meeting recording already running");
+        return Err("Meeting recording already running".to_string());
+    }
+
+    let recording = MeetingRecording::start(&recordings_dir(&app)?, conversation_id)?;
+    let info = recording.info();
+    *guard = Some(Arc::new(recording));
+
+    let _ = app.emit("meeting-recording-started", &info);
+    Ok(info)
+}
+
+#[tauri::command]
+pub async fn stop_meeting_recording(
+    app: AppHandle,
+) -> Result<Option<MeetingRecordingInfo>, String> {
+    let recording = app
+        .state::<crate::AudioState>()
+        .meeting_recording
+        .lock()
+        .map_err(|e| format!("Failed to acquire lock: {}", e))?
+        .take();
+
+    let Some(recording) = recording else {
+        return Ok(None);
+    };
+
+    let info = recording.finish()?;
+    let _ = app.emit("meeting-recording-stopped", &info);
+    Ok(Some(info))
+}
+
+#[tauri::command]
+pub async fn list_meeting_recordings(
+    app: AppHandle,
+    conversation_id: Option<String>,
+) -> Result<Vec<MeetingRecordingInfo>, String> {
+    let mut recordings = list_recordings(&recordings_dir(&app)?);
+    if let Some(conversation_id) = conversation_id {
+        recordings.retain(|r| r.conversation_id == conversation_id);
+    }
+    Ok(recordings)
+}
+
+#[tauri::command]
+pub async fn delete_meeting_recording(app: AppHandle, id: String) -> Result<(), String> {
+    let active = app
+        .state::<crate::AudioState>()
+        .meeting_recording
+        .lock()
+        .map_err(|e| format!("Failed to acquire lock: {}", e))?
+        .as_ref()
+        .is_some_and(|r| r.info().id == id);
+    if active {
+        return Err("Stop the recording before deleting it".to_string());
+    }
+
+    delete_recording(&recordings_dir(&app)?, &id)
+}
+
+// Decodes an audio or video file, splits it into utterances with the VAD and transcribes
+// each one like a live segment. Returns the import ID at once; the work continues in
+// the background with `audio-import-*` events.
+#[tauri::command]
+pub async fn import_audio_file(
+    app: AppHandle,
+    path: String,
+    vad_config: Option<VadConfig>,
+) -> Result<u64, String> {
+    let config = match vad_config {
+        Some(config) => {
+            config.validate()?;
+            config
+        }
+        None => app
+            .state::<crate::AudioState>()
+            .vad_config
+            .lock()
+            .map_err(|e| format!("Failed to read VAD config: {}", e))?
+            .clone(),
+    };
+    start_import(app, path, config).await
+}
+
+// Stops a running import; segments already transcribed stay emitted
+#[tauri::command]
+pub fn cancel_audio_import(app: AppHandle, import_id: u64) -> Result<(), String> {
+    cancel_import(&app, import_id)
+}
+
+#[tauri::command]
+pub async fn manual_stop_continuous(app: AppHandle) -> Result<(), String> {
+    let _ = app.emit("manual-stop-continuous", ());
 
     tokio::time::sleep(tokio::time::Duration::from_millis(20)).await;
 
     Ok(())
 }
 
+// Stops adding audio to the continuous recording without ending it
+#[tauri::command]
+pub async fn pause_continuous(app: AppHandle) -> Result<(), String> {
+    let _ = app.emit("pause-continuous", ());
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn resume_continuous(app: AppHandle) -> Result<(), String> {
+    let _ = app.emit("resume-continuous", ());
+    Ok(())
+}
+
 #[tauri::command]
 pub fn check_system_audio_access(_app: AppHandle) -> Result<bool, String> {
     match SpeakerInput::new() {
@@ -569,13 +1950,7 @@ pub async fn get_vad_config(app: AppHandle) -> Result<VadConfig, String> {
 
 #[tauri::command]
 pub async fn update_vad_config(app: AppHandle, config: VadConfig) -> Result<(), String> {
-    // Validate config
-    if config.sensitivity_rms < 0.0 || config.sensitivity_rms > 1.0 {
-        return Err("Invalid sensitivity_rms: must be 0.0-1.0".to_string());
-    }
-    if config.max_recording_duration_secs > 3600 {
-        return Err("Invalid max_recording_duration_secs: must be <= 3600 (1 hour)".to_string());
-    }
+    config.validate()?;
 
     let state = app.state::<crate::AudioState>();
     *state
@@ -586,6 +1961,26 @@ pub async fn update_vad_config(app: AppHandle, config: VadConfig) -> Result<(),
     Ok(())
 }
 
+// Encoded audio of a segment from a speech event, as raw bytes (no base64)
+#[tauri::command]
+pub fn get_audio_segment(app: AppHandle, segment_id: u64) -> Result<tauri::ipc::Response, String> {
+    let segment = segment_audio(&app, segment_id)?;
+    Ok(tauri::ipc::Response::new(segment.to_vec()))
+}
+
+// Frees a segment once the UI is done with it; old segments are also evicted automatically
+#[tauri::command]
+pub fn release_audio_segment(app: AppHandle, segment_id: u64) {
+    app.state::<crate::AudioState>().segments.remove(segment_id);
+}
+
+pub fn segment_audio(app: &AppHandle, segment_id: u64) -> Result<Arc<[u8]>, String> {
+    app.state::<crate::AudioState>()
+        .segments
+        .get(segment_id)
+        .ok_or_else(|| format!("Audio segment {} is no longer available", segment_id))
+}
+
 #[tauri::command]
 pub async fn get_capture_status(app: AppHandle) -> Result<bool, String> {
     let state = app.state::<crate::AudioState>();
@@ -596,6 +1991,44 @@ pub async fn get_capture_status(app: AppHandle) -> Result<bool, String> {
     Ok(is_capturing)
 }
 
+// Lists system output capture devices followed by microphones
+#[tauri::command]
+pub async fn list_audio_devices() -> Result<Vec<AudioDevice>, String> {
+    // Device enumeration blocks on the platform APIs
+    tokio::task::spawn_blocking(|| {
+        let mut devices = SpeakerInput::list_devices().map_err(|e| {
+            error!("Failed to list output devices: {}", e);
+            format!("Failed to list output devices: {}", e)
+        })?;
+
+        match MicInput::list_devices() {
+            Ok(inputs) => devices.extend(inputs),
+            Err(e) => warn!("Failed to list input devices: {}", e),
+        }
+
+        Ok(devices)
+    })
+    .await
+    .map_err(|e| format!("Device enumeration task failed: {}", e))?
+}
+
+// Health of the current (or most recent) system and microphone captures
+#[tauri::command]
+pub fn get_audio_diagnostics(app: AppHandle) -> Result<Vec<AudioDiagnostics>, String> {
+    let state = app.state::<crate::AudioState>();
+    let monitors = state
+        .capture_monitors
+        .lock()
+        .map_err(|e| format!("Failed to read diagnostics: {}", e))?;
+
+    let mut diagnostics: Vec<AudioDiagnostics> = monitors
+        .values()
+        .map(|monitor| monitor.snapshot())
+        .collect();
+    diagnostics.sort_by_key(|d| d.source != AudioSource::System);
+    Ok(diagnostics)
+}
+
 #[tauri::command]
 pub fn get_audio_sample_rate(_app: AppHandle) -> Result<u32, String> {
     let input = SpeakerInput::new().map_err(|e| {
@@ -608,3 +2041,184 @@ pub fn get_audio_sample_rate(_app: AppHandle) -> Result<u32, String> {
 
     Ok(sr)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use tauri::test::{mock_builder, mock_context, noop_assets};
+
+    #[test]
+    fn continuous_chunks_advance_when_overlap_covers_the_chunk() {
+        let app = mock_builder()
+            .manage(crate::AudioState::default())
+            .build(mock_context(noop_assets()))
+            .expect("Failed to build mock app");
+        let overlaps = Arc::new(Mutex::new(Vec::new()));
+        let received = overlaps.clone();
+        app.listen_any("continuous-chunk", move |event| {
+            let chunk: serde_json::Value = serde_json::from_str(event.payload()).unwrap();
+            received
+                .lock()
+                .unwrap()
+                .push(chunk["overlap_ms"].as_u64().unwrap());
+        });
+
+        // Rejected by validate(); the chunker still has to make progress
+        let config = VadConfig {
+            continuous_chunk_secs: 1,
+            continuous_chunk_overlap_ms: 1500,
+            ..VadConfig::default()
+        };
+        let mut chunks = ContinuousChunks::new(&config, 16_000);
+        let mut buffer = vec![0.1; 40_000];
+        chunks.flush_full(app.handle(), &mut buffer);
+
+        // Overlap is clamped to one sample short of a chunk
+        assert_eq!(*overlaps.lock().unwrap(), [0, 999]);
+        assert_eq!(buffer.len(), 40_000 - 1 - 16_000);
+    }
+
+    fn rms_of(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    // Sum of sines scaled to the given RMS
+    fn tones(freqs: &[f32], len: usize, rms: f32) -> Vec<f32> {
+        let samples: Vec<f32> = (0..len)
+            .map(|i| {
+                let t = i as f32 / 16_000.0;
+                freqs
+                    .iter()
+                    .map(|f| (2.0 * std::f32::consts::PI * f * t).sin())
+                    .sum()
+            })
+            .collect();
+        let scale = rms / rms_of(&samples);
+        samples.iter().map(|s| s * scale).collect()
+    }
+
+    // White noise from a fixed seed, scaled to the given RMS
+    fn white_noise(len: usize, rms: f32) -> Vec<f32> {
+        let mut state = 0x2545_f491u32;
+        let samples: Vec<f32> = (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as f32 / u32::MAX as f32 * 2.0 - 1.0
+            })
+            .collect();
+        let scale = rms / rms_of(&samples);
+        samples.iter().map(|s| s * scale).collect()
+    }
+
+    fn spectral_verdict(chunk: &[f32]) -> bool {
+        let mut detector = SpectralDetector::new(16_000, chunk.len());
+        detector.is_speech(chunk, rms_of(chunk), 0.0)
+    }
+
+    #[test]
+    fn spectral_detector_accepts_a_voiced_speech_band_tone() {
+        // Harmonics of a 200 Hz voice, all inside the speech band
+        let harmonics: Vec<f32> = (2..=16).map(|k| 200.0 * k as f32).collect();
+        assert!(spectral_verdict(&tones(&harmonics, 1024, 0.05)));
+    }
+
+    #[test]
+    fn spectral_detector_rejects_noise_at_the_same_rms() {
+        assert!(!spectral_verdict(&white_noise(1024, 0.05)));
+        // Mains hum sits below the speech band
+        assert!(!spectral_verdict(&tones(&[50.0, 100.0, 150.0], 1024, 0.05)));
+        // A single beep is too tonal
+        assert!(!spectral_verdict(&tones(&[1000.0], 1024, 0.05)));
+    }
+
+    #[test]
+    fn spectral_detector_clamps_bins_for_small_hops() {
+        for hop_size in [1, 2, 3, 8, 16, 32] {
+            let mut detector = SpectralDetector::new(16_000, hop_size);
+            let nyquist_bin = hop_size.next_power_of_two().max(2) / 2;
+            let (lo, hi) = detector.speech_bins;
+            assert!(1 <= lo && lo <= hi && hi <= nyquist_bin, "hop {}", hop_size);
+            assert!(detector.analysis_low_bin <= nyquist_bin);
+
+            // Must not index past the spectrum
+            let chunk = white_noise(hop_size, 0.05);
+            detector.is_speech(&chunk, rms_of(&chunk), 0.0);
+        }
+    }
+
+    #[test]
+    fn noise_floor_tracks_rising_and_falling_levels() {
+        let mut floor = NoiseFloor::new();
+        floor.update(0.01, false);
+        assert_eq!(floor.level(), 0.01);
+
+        for _ in 0..200 {
+            floor.update(0.02, false);
+        }
+        assert!((floor.level() - 0.02).abs() < 1e-4);
+
+        // Falls faster than it rises
+        floor.update(0.01, false);
+        let fallen = 0.02 - floor.level();
+        assert!(fallen > 0.01 * NOISE_FLOOR_SMOOTHING);
+        for _ in 0..50 {
+            floor.update(0.01, false);
+        }
+        assert!((floor.level() - 0.01).abs() < 1e-4);
+    }
+
+    #[test]
+    fn noise_floor_only_creeps_up_during_speech() {
+        let mut floor = NoiseFloor::new();
+        floor.update(0.01, false);
+
+        floor.update(0.2, true);
+        let crept = floor.level();
+        assert!(crept > 0.01 && crept < 0.011);
+
+        // Quiet gaps inside speech never lower it
+        floor.update(0.001, true);
+        assert_eq!(floor.level(), crept);
+    }
+
+    #[test]
+    fn adaptive_thresholds_stay_within_bounds() {
+        let config = VadConfig {
+            adaptive_thresholds: true,
+            ..VadConfig::default()
+        };
+        let with_floor = |level: f32| {
+            let mut floor = NoiseFloor::new();
+            floor.update(level, false);
+            floor.thresholds(&config)
+        };
+
+        // No measurement yet: the fixed values
+        let fixed = NoiseFloor::new().thresholds(&config);
+        assert!(!fixed.adaptive);
+        assert_eq!(fixed.sensitivity_rms, config.sensitivity_rms);
+
+        let typical = with_floor(0.01);
+        assert!(typical.adaptive);
+        assert!((typical.sensitivity_rms - 0.01 * config.adaptive_speech_ratio).abs() < 1e-6);
+        assert!((typical.noise_gate_threshold - 0.01 * config.adaptive_gate_ratio).abs() < 1e-6);
+
+        let quiet = with_floor(0.0001);
+        assert_eq!(quiet.sensitivity_rms, ADAPTIVE_MIN_SENSITIVITY_RMS);
+        assert_eq!(quiet.noise_gate_threshold, ADAPTIVE_MIN_GATE);
+
+        // The gate never rises above the speech threshold
+        let loud = with_floor(0.5);
+        assert_eq!(loud.sensitivity_rms, ADAPTIVE_MAX_SENSITIVITY_RMS);
+        assert_eq!(loud.noise_gate_threshold, ADAPTIVE_MAX_SENSITIVITY_RMS);
+        assert!(loud.peak_threshold <= 1.0);
+
+        // Off by default, even once the floor is known
+        let mut floor = NoiseFloor::new();
+        floor.update(0.5, false);
+        assert!(!floor.thresholds(&VadConfig::default()).adaptive);
+    }
+}
@@ -0,0 +1,191 @@
+// Meetwings noise suppression: STFT Wiener filter against a noise profile learned from non-speech audio
+use super::spectrum::{hann, Fft};
+
+const PROFILE_SMOOTHING: f32 = 0.1; // Per-frame weight of new noise spectra
+const GAIN_SMOOTHING: f32 = 0.5; // Blend with the previous frame's gain to limit musical noise
+
+pub struct NoiseSuppressor {
+    fft: Fft,
+    window: Vec<f32>,
+    strength: f32,   // Over-subtraction factor applied to the noise profile
+    gain_floor: f32, // Linear minimum gain per bin
+    profile: Option<Vec<f32>>,
+    learn_buffer: Vec<f32>,
+    re: Vec<f32>,
+    im: Vec<f32>,
+    gains: Vec<f32>,
+}
+
+impl NoiseSuppressor {
+    pub fn new(sample_rate: u32, strength: f32, floor_db: f32) -> Self {
+        // ~20ms analysis frames at 50% overlap
+        let size = if sample_rate > 24_000 { 1024 } else { 512 };
+        let fft = Fft::new(size);
+
+        Self {
+            window: hann(size),
+            strength,
+            gain_floor: 10f32.powf(floor_db / 20.0),
+            profile: None,
+            learn_buffer: Vec::with_capacity(size * 2),
+            re: vec![0.0; size],
+            im: vec![0.0; size],
+            gains: vec![1.0; size / 2 + 1],
+            fft,
+        }
+    }
+
+    // Feeds background-only audio into the noise profile
+    pub fn learn(&mut self, noise: &[f32]) {
+        let size = self.fft.size();
+        let hop = size / 2;
+        self.learn_buffer.extend_from_slice(noise);
+
+        let mut start = 0;
+        while start + size <= self.learn_buffer.len() {
+            self.analyze(start);
+            let (re, im) = (&self.re, &self.im);
+            let power = (0..=size / 2).map(|k| re[k] * re[k] + im[k] * im[k]);
+
+            match self.profile.as_mut() {
+                Some(profile) => {
+                    for (n, p) in profile.iter_mut().zip(power) {
+                        *n += (p - *n) * PROFILE_SMOOTHING;
+                    }
+                }
+                None => self.profile = Some(power.collect()),
+            }
+            start += hop;
+        }
+        self.learn_buffer.drain(..start);
+    }
+
+    // Returns the filtered signal; passes audio through until a profile has been learned
+    pub fn process(&mut self, samples: &[f32]) -> Vec<f32> {
+        let Some(profile) = self.profile.take() else {
+            return samples.to_vec();
+        };
+        let size = self.fft.size();
+        let hop = size / 2;
+
+        // Hann at 50% overlap sums to one, so pad by a hop on both ends and overlap-add
+        let mut padded = vec![0.0; hop];
+        padded.extend_from_slice(samples);
+        padded.resize(hop + samples.len() + size, 0.0);
+        let mut output = vec![0.0; padded.len()];
+        self.gains.fill(1.0);
+
+        let mut start = 0;
+        while start + size <= padded.len() {
+            for ((re, &s), &w) in self.re.iter_mut().zip(&padded[start..]).zip(&self.window) {
+                *re = s * w;
+            }
+            self.im.fill(0.0);
+            self.fft.forward(&mut self.re, &mut self.im);
+
+            for (k, &noise) in profile.iter().enumerate() {
+                let power = self.re[k] * self.re[k] + self.im[k] * self.im[k];
+                let snr = (power / (noise * self.strength + f32::EPSILON) - 1.0).max(0.0);
+                let gain = (snr / (1.0 + snr)).max(self.gain_floor);
+                let gain = self.gains[k] * GAIN_SMOOTHING + gain * (1.0 - GAIN_SMOOTHING);
+                self.gains[k] = gain;
+
+                // Real input: bins k and size - k are mirrored
+                self.re[k] *= gain;
+                self.im[k] *= gain;
+                if k > 0 && k < size / 2 {
+                    self.re[size - k] *= gain;
+                    self.im[size - k] *= gain;
+                }
+            }
+
+            self.fft.inverse(&mut self.re, &mut self.im);
+            for (out, &re) in output[start..start + size].iter_mut().zip(&self.re) {
+                *out += re;
+            }
+            start += hop;
+        }
+
+        self.profile = Some(profile);
+        output.drain(..hop);
+        output.truncate(samples.len());
+        output
+    }
+
+    fn analyze(&mut self, start: usize) {
+        let frame = &self.learn_buffer[start..start + self.fft.size()];
+        for ((re, &s), &w) in self.re.iter_mut().zip(frame).zip(&self.window) {
+            *re = s * w;
+        }
+        self.im.fill(0.0);
+        self.fft.forward(&mut self.re, &mut self.im);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::TAU;
+
+    const SAMPLE_RATE: u32 = 16_000;
+
+    // Deterministic white noise in [-amplitude, amplitude]
+    fn noise(len: usize, amplitude: f32, seed: u32) -> Vec<f32> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (state >> 8) as f32 / (1 << 24) as f32 * 2.0 * amplitude - amplitude
+            })
+            .collect()
+    }
+
+    fn tone(hz: f32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| 0.3 * (TAU * hz * i as f32 / SAMPLE_RATE as f32).sin())
+            .collect()
+    }
+
+    fn rms_db(samples: &[f32]) -> f32 {
+        let mean = samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32;
+        10.0 * mean.log10()
+    }
+
+    fn trained() -> NoiseSuppressor {
+        let mut suppressor = NoiseSuppressor::new(SAMPLE_RATE, 1.0, -20.0);
+        suppressor.learn(&noise(SAMPLE_RATE as usize, 0.05, 1));
+        suppressor
+    }
+
+    #[test]
+    fn stationary_noise_is_attenuated() {
+        let mut suppressor = trained();
+        let input = noise(SAMPLE_RATE as usize, 0.05, 2);
+        let output = suppressor.process(&input);
+
+        let reduction = rms_db(&input) - rms_db(&output);
+        assert!(reduction > 6.0, "noise reduced by only {:.1} dB", reduction);
+    }
+
+    #[test]
+    fn tone_is_kept() {
+        let mut suppressor = trained();
+        let input: Vec<f32> = tone(1000.0, SAMPLE_RATE as usize)
+            .iter()
+            .zip(noise(SAMPLE_RATE as usize, 0.05, 3))
+            .map(|(t, n)| t + n)
+            .collect();
+        let output = suppressor.process(&input);
+
+        let change = rms_db(&output) - rms_db(&tone(1000.0, SAMPLE_RATE as usize));
+        assert!(change.abs() < 3.0, "tone level changed by {:.1} dB", change);
+    }
+
+    #[test]
+    fn output_length_matches_input() {
+        let mut suppressor = trained();
+        for len in [0, 1, 255, 257, 1000, 4097] {
+            assert_eq!(suppressor.process(&noise(len, 0.05, 4)).len(), len);
+        }
+    }
+}
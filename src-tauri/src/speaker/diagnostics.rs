@@ -0,0 +1,139 @@
+// Meetwings capture diagnostics: per-capture health for get_audio_diagnostics / audio-stats
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use super::queue::QueueHandle;
+use super::AudioSource;
+
+const LEVEL_WINDOW_MS: u32 = 100; // One RMS history entry per window
+const RMS_HISTORY_LEN: usize = 100; // 10s of history
+
+// Snapshot sent by get_audio_diagnostics and the `audio-stats` event
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioDiagnostics {
+    pub source: AudioSource,
+    pub backend: &'static str,
+    pub device: Option<String>,
+    pub sample_rate: u32,
+    pub running: bool,
+    pub samples_received: u64,
+    pub samples_dropped: u64,
+    pub overflows: u64,
+    pub queue_depth: u64,
+    pub queue_capacity: usize,
+    pub last_sample_age_ms: Option<u64>,
+    pub rms_history: Vec<f32>,        // Oldest first, pre-gate input level
+    pub noise: Option<NoiseEstimate>, // VAD captures only
+    pub last_error: Option<String>,
+}
+
+// Background level and the VAD thresholds currently in effect
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct NoiseEstimate {
+    pub noise_floor: f32, // RMS of the raw input during non-speech
+    pub sensitivity_rms: f32,
+    pub peak_threshold: f32,
+    pub noise_gate_threshold: f32,
+    pub adaptive: bool, // False while the fixed VadConfig values are used
+}
+
+struct LevelHistory {
+    sum_squares: f32,
+    count: usize,
+    history: VecDeque<f32>,
+}
+
+// Health of one running capture, shared between its capture loop and the commands
+pub struct CaptureMonitor {
+    source: AudioSource,
+    backend: &'static str,
+    sample_rate: u32,
+    queue: QueueHandle,
+    levels: Mutex<LevelHistory>,
+    noise: Mutex<Option<NoiseEstimate>>,
+}
+
+impl CaptureMonitor {
+    pub fn new(
+        source: AudioSource,
+        backend: &'static str,
+        sample_rate: u32,
+        queue: QueueHandle,
+    ) -> Self {
+        Self {
+            source,
+            backend,
+            sample_rate,
+            queue,
+            levels: Mutex::new(LevelHistory {
+                sum_squares: 0.0,
+                count: 0,
+                history: VecDeque::with_capacity(RMS_HISTORY_LEN),
+            }),
+            noise: Mutex::new(None),
+        }
+    }
+
+    pub fn source(&self) -> AudioSource {
+        self.source
+    }
+
+    pub fn is_running(&self) -> bool {
+        !self.queue.is_closed()
+    }
+
+    // Feeds samples the capture loop has read into the RMS history
+    pub fn record_level(&self, samples: &[f32]) {
+        let window = (self.sample_rate * LEVEL_WINDOW_MS / 1000).max(1) as usize;
+        let mut levels = self.levels.lock().unwrap();
+
+        for &s in samples {
+            levels.sum_squares += s * s;
+            levels.count += 1;
+
+            if levels.count >= window {
+                let rms = (levels.sum_squares / levels.count as f32).sqrt();
+                if levels.history.len() == RMS_HISTORY_LEN {
+                    levels.history.pop_front();
+                }
+                levels.history.push_back(rms);
+                levels.sum_squares = 0.0;
+                levels.count = 0;
+            }
+        }
+    }
+
+    pub fn record_noise(&self, estimate: NoiseEstimate) {
+        *self.noise.lock().unwrap() = Some(estimate);
+    }
+
+    pub fn snapshot(&self) -> AudioDiagnostics {
+        let stats = self.queue.stats();
+        let rms_history = self
+            .levels
+            .lock()
+            .unwrap()
+            .history
+            .iter()
+            .copied()
+            .collect();
+
+        AudioDiagnostics {
+            source: self.source,
+            backend: self.backend,
+            device: self.queue.source(),
+            sample_rate: self.sample_rate,
+            running: self.is_running(),
+            samples_received: stats.received,
+            samples_dropped: stats.dropped,
+            overflows: stats.overflows,
+            queue_depth: stats.queued,
+            queue_capacity: stats.capacity,
+            last_sample_age_ms: stats.last_sample_age_ms,
+            rms_history,
+            noise: *self.noise.lock().unwrap(),
+            last_error: self.queue.last_error(),
+        }
+    }
+}
@@ -0,0 +1,167 @@
+// Meetwings channel handling: downmix strategies for multichannel captures
+use serde::{Deserialize, Serialize};
+
+// How interleaved frames are folded into the mono stream the pipeline consumes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Downmix {
+    #[default]
+    Average, // Mean of all channels (previous mono behaviour)
+    Left,
+    Right,
+    MaxEnergy, // Loudest channel per captured block
+}
+
+// What SpeakerStream yields
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelMode {
+    Mono(Downmix),
+    #[allow(dead_code)] // For consumers that separate channels themselves
+    Interleaved,
+}
+
+impl Default for ChannelMode {
+    fn default() -> Self {
+        ChannelMode::Mono(Downmix::Average)
+    }
+}
+
+impl ChannelMode {
+    // Whether the platform has to capture every channel (mono averaging can be left to the OS)
+    pub fn needs_native_channels(&self) -> bool {
+        !matches!(self, ChannelMode::Mono(Downmix::Average))
+    }
+
+    // Applies the mode to one captured block of interleaved samples
+    pub fn apply(&self, interleaved: &[f32], channels: usize) -> Vec<f32> {
+        let mut out = Vec::new();
+        self.apply_into(interleaved, channels, &mut out);
+        out
+    }
+
+    // Same as apply, reusing `out` so audio callbacks don't allocate per block
+    pub fn apply_into(&self, interleaved: &[f32], channels: usize, out: &mut Vec<f32>) {
+        match self {
+            ChannelMode::Mono(strategy) => downmix_into(interleaved, channels, *strategy, out),
+            ChannelMode::Interleaved => {
+                out.clear();
+                out.extend_from_slice(interleaved);
+            }
+        }
+    }
+
+    // Channel count of the samples produced from a `channels`-wide capture
+    pub fn output_channels(&self, channels: usize) -> usize {
+        match self {
+            ChannelMode::Mono(_) => 1,
+            ChannelMode::Interleaved => channels.max(1),
+        }
+    }
+}
+
+pub fn downmix(interleaved: &[f32], channels: usize, strategy: Downmix) -> Vec<f32> {
+    let mut out = Vec::new();
+    downmix_into(interleaved, channels, strategy, &mut out);
+    out
+}
+
+// Downmixes into `out`, replacing its contents
+pub fn downmix_into(interleaved: &[f32], channels: usize, strategy: Downmix, out: &mut Vec<f32>) {
+    out.clear();
+    if channels <= 1 {
+        out.extend_from_slice(interleaved);
+        return;
+    }
+
+    let frames = interleaved.chunks_exact(channels);
+    match strategy {
+        Downmix::Average => {
+            out.extend(frames.map(|frame| frame.iter().sum::<f32>() / channels as f32))
+        }
+        Downmix::Left => out.extend(frames.map(|frame| frame[0])),
+        Downmix::Right => out.extend(frames.map(|frame| frame[1])),
+        Downmix::MaxEnergy => {
+            let energy = |channel: usize| {
+                interleaved
+                    .chunks_exact(channels)
+                    .map(|frame| frame[channel] * frame[channel])
+                    .sum::<f32>()
+            };
+            let loudest = (0..channels)
+                .map(|channel| (channel, energy(channel)))
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(channel, _)| channel)
+                .unwrap_or(0);
+
+            out.extend(frames.map(|frame| frame[loudest]));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Three stereo frames: left ramps up, right is quieter and inverted
+    const STEREO: [f32; 6] = [0.1, -0.05, 0.2, -0.1, 0.3, -0.15];
+
+    #[test]
+    fn average_mixes_every_channel() {
+        let mono = downmix(&STEREO, 2, Downmix::Average);
+        assert_eq!(mono.len(), 3);
+        for (m, expected) in mono.iter().zip([0.025, 0.05, 0.075]) {
+            assert!((m - expected).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn left_and_right_pick_one_channel() {
+        assert_eq!(downmix(&STEREO, 2, Downmix::Left), [0.1, 0.2, 0.3]);
+        assert_eq!(downmix(&STEREO, 2, Downmix::Right), [-0.05, -0.1, -0.15]);
+    }
+
+    #[test]
+    fn max_energy_picks_the_loudest_channel_of_the_block() {
+        assert_eq!(downmix(&STEREO, 2, Downmix::MaxEnergy), [0.1, 0.2, 0.3]);
+
+        let right_louder = [0.01, 0.5, 0.02, -0.4];
+        assert_eq!(downmix(&right_louder, 2, Downmix::MaxEnergy), [0.5, -0.4]);
+    }
+
+    #[test]
+    fn mono_input_passes_through() {
+        for strategy in [
+            Downmix::Average,
+            Downmix::Left,
+            Downmix::Right,
+            Downmix::MaxEnergy,
+        ] {
+            assert_eq!(downmix(&STEREO, 1, strategy), STEREO);
+        }
+    }
+
+    #[test]
+    fn interleaved_keeps_every_channel() {
+        let mode = ChannelMode::Interleaved;
+        assert!(mode.needs_native_channels());
+        assert_eq!(mode.output_channels(2), 2);
+        assert_eq!(mode.apply(&STEREO, 2), STEREO);
+    }
+
+    #[test]
+    fn apply_into_replaces_the_scratch_contents() {
+        let mut out = vec![9.0; 8];
+        ChannelMode::Mono(Downmix::Left).apply_into(&STEREO, 2, &mut out);
+        assert_eq!(out, [0.1, 0.2, 0.3]);
+
+        ChannelMode::Interleaved.apply_into(&STEREO[..2], 2, &mut out);
+        assert_eq!(out, [0.1, -0.05]);
+    }
+
+    #[test]
+    fn only_averaged_mono_is_left_to_the_platform() {
+        assert!(!ChannelMode::default().needs_native_channels());
+        assert!(ChannelMode::Mono(Downmix::Left).needs_native_channels());
+        assert_eq!(ChannelMode::Mono(Downmix::MaxEnergy).output_channels(6), 1);
+    }
+}
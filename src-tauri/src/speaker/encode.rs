@@ -0,0 +1,105 @@
+// Meetwings segment encoders: the container used for speech-detected / speech-partial
+// audio, and detection of it again when the bytes come back for upload
+use hound::{SampleFormat, WavSpec, WavWriter};
+use serde::{Deserialize, Serialize};
+use std::io::Cursor;
+use tracing::error;
+
+use super::flac::encode_flac;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AudioEncoding {
+    #[default]
+    Wav, // 16-bit PCM
+    Flac, // Lossless, about half the size of WAV for speech
+    Opus, // Ogg Opus at 24 kbps, ~20x smaller; needs the `opus` cargo feature
+}
+
+impl AudioEncoding {
+    // Whether this build can produce the format
+    pub fn is_available(self) -> bool {
+        self != Self::Opus || cfg!(feature = "opus")
+    }
+
+    // Sniffs the container from its magic bytes
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        match bytes.get(..4)? {
+            b"RIFF" => Some(Self::Wav),
+            b"fLaC" => Some(Self::Flac),
+            b"OggS" => Some(Self::Opus),
+            _ => None,
+        }
+    }
+
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            Self::Wav => "audio/wav",
+            Self::Flac => "audio/flac",
+            Self::Opus => "audio/ogg",
+        }
+    }
+
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::Wav => "audio.wav",
+            Self::Flac => "audio.flac",
+            Self::Opus => "audio.ogg",
+        }
+    }
+}
+
+// Encodes mono samples already at `sample_rate`
+pub fn encode(
+    encoding: AudioEncoding,
+    sample_rate: u32,
+    samples: &[f32],
+) -> Result<Vec<u8>, String> {
+    match encoding {
+        AudioEncoding::Wav => encode_wav(sample_rate, samples),
+        AudioEncoding::Flac => Ok(encode_flac(&to_i16(samples), sample_rate)),
+        AudioEncoding::Opus => encode_opus(sample_rate, samples),
+    }
+}
+
+#[cfg(feature = "opus")]
+fn encode_opus(sample_rate: u32, samples: &[f32]) -> Result<Vec<u8>, String> {
+    super::opus::encode_ogg_opus(samples, sample_rate)
+}
+
+// update_vad_config rejects Opus in these builds; fall back to the next smallest format
+#[cfg(not(feature = "opus"))]
+fn encode_opus(sample_rate: u32, samples: &[f32]) -> Result<Vec<u8>, String> {
+    tracing::warn!("Opus encoding is not available in this build, using FLAC");
+    encode(AudioEncoding::Flac, sample_rate, samples)
+}
+
+fn encode_wav(sample_rate: u32, samples: &[f32]) -> Result<Vec<u8>, String> {
+    let mut cursor = Cursor::new(Vec::new());
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+
+    let mut writer = WavWriter::new(&mut cursor, spec).map_err(|e| {
+        error!("Failed to create WAV writer: {}", e);
+        e.to_string()
+    })?;
+
+    for sample in to_i16(samples) {
+        writer.write_sample(sample).map_err(|e| e.to_string())?;
+    }
+
+    writer.finalize().map_err(|e| e.to_string())?;
+
+    Ok(cursor.into_inner())
+}
+
+fn to_i16(samples: &[f32]) -> Vec<i16> {
+    samples
+        .iter()
+        .map(|&s| (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)
+        .collect()
+}
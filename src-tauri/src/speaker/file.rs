@@ -0,0 +1,299 @@
+// Meetwings file and generator inputs: stand-ins for a capture device, selected with
+// `file:///path/to/audio.wav` or `generator://<pattern>` device IDs. Used for
+// reproducible VAD runs and tests; they behave like a device that stops at the end.
+use anyhow::{anyhow, Result};
+use std::f32::consts::TAU;
+use std::path::{Path, PathBuf};
+use std::task::Poll;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::downmix::ChannelMode;
+use super::frames::AudioFrame;
+use super::queue::{sample_queue, QueueHandle, SampleConsumer, SampleProducer, QUEUE_CAPACITY};
+
+const FILE_SCHEME: &str = "file://";
+const GENERATOR_SCHEME: &str = "generator://";
+const GENERATOR_SAMPLE_RATE: u32 = 16_000;
+const PUSH_BLOCK_MS: u64 = 20; // Paced inputs deliver audio in blocks of this length
+const FULL_QUEUE_WAIT: Duration = Duration::from_millis(1);
+const TONE_AMPLITUDE: f32 = 0.3;
+const NOISE_AMPLITUDE: f32 = 0.02;
+
+pub struct FileInput {
+    name: String,      // Reported as the capture source
+    samples: Vec<f32>, // Interleaved, loaded up front so bad input fails in new()
+    sample_rate: u32,
+    channels: usize,
+    realtime: bool, // Deliver at playback speed (like a device) instead of as fast as it is read
+    channel_mode: ChannelMode,
+}
+
+impl FileInput {
+    // Whether a device ID selects a file or generator input instead of a real device
+    pub fn handles(device_id: &str) -> bool {
+        device_id.starts_with(FILE_SCHEME) || device_id.starts_with(GENERATOR_SCHEME)
+    }
+
+    // Accepts `file:///path.wav` and `generator://<steps>`, each optionally followed by
+    // `?realtime=false` and (generators only) `rate=<hz>`. Paths can't contain '?'.
+    pub fn new(device_id: &str) -> Result<Self> {
+        let (target, query) = device_id.split_once('?').unwrap_or((device_id, ""));
+
+        let mut realtime = true;
+        let mut sample_rate = None;
+        for option in query.split('&').filter(|o| !o.is_empty()) {
+            match option.split_once('=') {
+                Some(("realtime", value)) => {
+                    realtime = value
+                        .parse()
+                        .map_err(|_| anyhow!("Invalid realtime option: {}", value))?;
+                }
+                Some(("rate", value)) => {
+                    sample_rate = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|rate| (8000..=96000).contains(rate))
+                            .ok_or_else(|| anyhow!("Invalid generator sample rate: {}", value))?,
+                    );
+                }
+                _ => return Err(anyhow!("Unknown audio input option: {}", option)),
+            }
+        }
+
+        let (name, (samples, sample_rate, channels)) =
+            if let Some(path) = target.strip_prefix(FILE_SCHEME) {
+                if sample_rate.is_some() {
+                    return Err(anyhow!("WAV files play at their own sample rate"));
+                }
+                let path = file_path(path);
+                let loaded = read_wav(&path)?;
+                (path.to_string_lossy().into_owned(), loaded)
+            } else if let Some(pattern) = target.strip_prefix(GENERATOR_SCHEME) {
+                let sample_rate = sample_rate.unwrap_or(GENERATOR_SAMPLE_RATE);
+                let samples = generate(pattern, sample_rate)?;
+                (
+                    format!("{}{}", GENERATOR_SCHEME, pattern),
+                    (samples, sample_rate, 1),
+                )
+            } else {
+                return Err(anyhow!("Not a file or generator device: {}", device_id));
+            };
+
+        Ok(Self {
+            name,
+            samples,
+            sample_rate,
+            channels,
+            realtime,
+            channel_mode: ChannelMode::default(),
+        })
+    }
+
+    pub fn set_channel_mode(&mut self, mode: ChannelMode) {
+        self.channel_mode = mode;
+    }
+
+    pub fn stream(self) -> FileStream {
+        let (producer, consumer) = sample_queue(QUEUE_CAPACITY);
+        consumer.handle().set_source(&self.name);
+
+        let Self {
+            samples,
+            sample_rate,
+            channels,
+            realtime,
+            channel_mode,
+            ..
+        } = self;
+        let playback_thread = thread::spawn(move || {
+            play(
+                producer,
+                &samples,
+                sample_rate,
+                channels,
+                channel_mode,
+                realtime,
+            )
+        });
+
+        FileStream {
+            queue: consumer,
+            playback_thread: Some(playback_thread),
+            sample_rate,
+            channels: channel_mode.output_channels(channels),
+        }
+    }
+}
+
+pub struct FileStream {
+    queue: SampleConsumer,
+    playback_thread: Option<thread::JoinHandle<()>>,
+    sample_rate: u32,
+    channels: usize,
+}
+
+impl FileStream {
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn backend(&self) -> &'static str {
+        "file"
+    }
+
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    pub fn queue(&self) -> &QueueHandle {
+        self.queue.handle()
+    }
+
+    pub fn poll_sample(&mut self, cx: &mut std::task::Context<'_>) -> Poll<Option<f32>> {
+        self.queue.poll_sample(cx)
+    }
+
+    pub fn poll_frame(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+        frame_size: usize,
+    ) -> Poll<Option<AudioFrame>> {
+        self.queue.poll_frame(cx, frame_size, self.sample_rate)
+    }
+}
+
+impl Drop for FileStream {
+    fn drop(&mut self) {
+        // Closing the queue also stops the playback thread early
+        self.queue.handle().close();
+        if let Some(thread) = self.playback_thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+// Feeds the queue until the audio ends or the stream is dropped, then closes it
+fn play(
+    mut producer: SampleProducer,
+    interleaved: &[f32],
+    sample_rate: u32,
+    channels: usize,
+    channel_mode: ChannelMode,
+    realtime: bool,
+) {
+    let block_frames = (sample_rate as u64 * PUSH_BLOCK_MS / 1000).max(1) as usize;
+    let started = Instant::now();
+    let mut played_frames = 0u64;
+
+    for block in interleaved.chunks(block_frames * channels) {
+        let samples = channel_mode.apply(block, channels);
+
+        if realtime {
+            let due = Duration::from_micros(played_frames * 1_000_000 / sample_rate as u64);
+            if let Some(wait) = due.checked_sub(started.elapsed()) {
+                thread::sleep(wait);
+            }
+        } else {
+            // Unlike a device this input can wait, so never overflow the queue
+            while !producer.handle().is_closed() {
+                let stats = producer.handle().stats();
+                if stats.queued as usize + samples.len() <= stats.capacity {
+                    break;
+                }
+                thread::sleep(FULL_QUEUE_WAIT);
+            }
+        }
+
+        if producer.handle().is_closed() {
+            return;
+        }
+        producer.push(&samples);
+        played_frames += (block.len() / channels) as u64;
+    }
+
+    producer.handle().close();
+}
+
+// `file:///C:/audio.wav` names a Windows path; drop the slash before the drive letter
+fn file_path(path: &str) -> PathBuf {
+    let bytes = path.as_bytes();
+    if bytes.len() > 2 && bytes[0] == b'/' && bytes[1].is_ascii_alphabetic() && bytes[2] == b':' {
+        PathBuf::from(&path[1..])
+    } else {
+        PathBuf::from(path)
+    }
+}
+
+// Interleaved samples, sample rate and channel count of a WAV file
+fn read_wav(path: &Path) -> Result<(Vec<f32>, u32, usize)> {
+    let mut reader = hound::WavReader::open(path)
+        .map_err(|e| anyhow!("Failed to open {}: {}", path.display(), e))?;
+    let spec = reader.spec();
+
+    let samples = match spec.sample_format {
+        hound::SampleFormat::Float => reader.samples::<f32>().collect::<Result<Vec<_>, _>>(),
+        hound::SampleFormat::Int => {
+            let scale = 1.0 / (1i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|s| s.map(|s| s as f32 * scale))
+                .collect()
+        }
+    }
+    .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+
+    if spec.channels == 0 || !(8000..=96000).contains(&spec.sample_rate) {
+        return Err(anyhow!(
+            "Unsupported WAV format in {}: {} channels at {} Hz",
+            path.display(),
+            spec.channels,
+            spec.sample_rate
+        ));
+    }
+
+    Ok((samples, spec.sample_rate, spec.channels as usize))
+}
+
+// Renders a comma-separated list of steps, each `<kind>:<args>` with durations in ms:
+// `silence:<ms>`, `tone:<hz>:<ms>[:<amplitude>]`, `noise:<ms>[:<amplitude>]`
+fn generate(pattern: &str, sample_rate: u32) -> Result<Vec<f32>> {
+    let mut samples = Vec::new();
+    let mut noise_state = 0x2545_f491u32; // Fixed seed so runs are reproducible
+
+    for step in pattern.split(',').filter(|s| !s.is_empty()) {
+        let mut args = step.split(':');
+        let kind = args.next().unwrap_or_default();
+        let values = args
+            .map(|a| a.parse::<f32>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| anyhow!("Invalid generator step: {}", step))?;
+        let len = |ms: f32| (ms.max(0.0) * sample_rate as f32 / 1000.0) as usize;
+
+        match (kind, values.as_slice()) {
+            ("silence", [ms]) => samples.resize(samples.len() + len(*ms), 0.0),
+            ("tone", [hz, ms, rest @ ..]) if rest.len() <= 1 => {
+                let amplitude = rest.first().copied().unwrap_or(TONE_AMPLITUDE);
+                let step = TAU * hz / sample_rate as f32;
+                samples.extend((0..len(*ms)).map(|i| amplitude * (step * i as f32).sin()));
+            }
+            ("noise", [ms, rest @ ..]) if rest.len() <= 1 => {
+                let amplitude = rest.first().copied().unwrap_or(NOISE_AMPLITUDE);
+                samples.extend((0..len(*ms)).map(|_| {
+                    // xorshift32
+                    noise_state ^= noise_state << 13;
+                    noise_state ^= noise_state >> 17;
+                    noise_state ^= noise_state << 5;
+                    amplitude * (noise_state as f32 / u32::MAX as f32 * 2.0 - 1.0)
+                }));
+            }
+            _ => return Err(anyhow!("Invalid generator step: {}", step)),
+        }
+    }
+
+    if samples.is_empty() {
+        return Err(anyhow!("Generator pattern is empty: {}", pattern));
+    }
+    Ok(samples)
+}
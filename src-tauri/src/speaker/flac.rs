@@ -0,0 +1,401 @@
+// Meetwings FLAC encoder: 16-bit mono, fixed predictors (orders 0-4) with
+// partitioned Rice residuals. Lossless at roughly half the size of WAV for speech.
+const BLOCK_SIZE: usize = 4096;
+const MAX_FIXED_ORDER: usize = 4;
+const MAX_PARTITION_ORDER: u32 = 6;
+const MAX_RICE_PARAM: u32 = 14; // 4-bit parameters; 15 is the escape code
+
+pub fn encode_flac(samples: &[i16], sample_rate: u32) -> Vec<u8> {
+    let mut out = BitWriter::with_capacity(samples.len() + 64);
+
+    out.write_bytes(b"fLaC");
+    // STREAMINFO, the only (and so last) metadata block
+    out.write(1, 1);
+    out.write(0, 7);
+    out.write(34, 24);
+    out.write(BLOCK_SIZE as u64, 16); // Min block size (the last block may be shorter)
+    out.write(BLOCK_SIZE as u64, 16);
+    out.write(0, 24); // Min / max frame size unknown
+    out.write(0, 24);
+    out.write(sample_rate as u64, 20);
+    out.write(0, 3); // Channels - 1
+    out.write(15, 5); // Bits per sample - 1
+    out.write(samples.len() as u64, 36);
+    out.write_bytes(&[0; 16]); // MD5 not computed
+
+    let mut residual = Vec::with_capacity(BLOCK_SIZE);
+    for (index, block) in samples.chunks(BLOCK_SIZE).enumerate() {
+        write_frame(&mut out, index as u64, block, &mut residual);
+    }
+
+    out.into_bytes()
+}
+
+fn write_frame(out: &mut BitWriter, index: u64, block: &[i16], residual: &mut Vec<i32>) {
+    let start = out.byte_len();
+
+    out.write(0b11_1111_1111_1110, 14); // Sync code
+    out.write(0, 1);
+    out.write(0, 1); // Fixed block size stream
+    out.write(0b0111, 4); // Block size - 1 follows as 16 bits
+    out.write(0b0000, 4); // Sample rate from STREAMINFO
+    out.write(0b0000, 4); // Mono
+    out.write(0b100, 3); // 16 bits per sample
+    out.write(0, 1);
+    write_utf8_number(out, index);
+    out.write(block.len() as u64 - 1, 16);
+    let crc = crc8(&out.bytes()[start..]);
+    out.write(crc as u64, 8);
+
+    write_subframe(out, block, residual);
+
+    out.align();
+    let crc = crc16(&out.bytes()[start..]);
+    out.write(crc as u64, 16);
+}
+
+fn write_subframe(out: &mut BitWriter, block: &[i16], residual: &mut Vec<i32>) {
+    out.write(0, 1);
+
+    if block.iter().all(|&s| s == block[0]) {
+        out.write(0b000000, 6);
+        out.write(0, 1);
+        out.write_signed(block[0] as i32, 16);
+        return;
+    }
+
+    // Cheapest fixed predictor, or verbatim when nothing beats raw samples
+    let mut best: Option<(usize, u32, u64)> = None;
+    for order in 0..=MAX_FIXED_ORDER.min(block.len() - 1) {
+        fixed_residual(block, order, residual);
+        let (partition_order, bits) = best_partitioning(residual, block.len(), order);
+        let bits = bits + order as u64 * 16;
+        if best.is_none_or(|(_, _, best_bits)| bits < best_bits) {
+            best = Some((order, partition_order, bits));
+        }
+    }
+
+    match best {
+        Some((order, partition_order, bits)) if bits < block.len() as u64 * 16 => {
+            out.write(0b001000 | order as u64, 6);
+            out.write(0, 1);
+            for &s in &block[..order] {
+                out.write_signed(s as i32, 16);
+            }
+            fixed_residual(block, order, residual);
+            write_residual(out, residual, block.len(), order, partition_order);
+        }
+        _ => {
+            out.write(0b000001, 6);
+            out.write(0, 1);
+            for &s in block {
+                out.write_signed(s as i32, 16);
+            }
+        }
+    }
+}
+
+// Prediction error of the order-N polynomial predictor, for samples N..
+fn fixed_residual(block: &[i16], order: usize, residual: &mut Vec<i32>) {
+    residual.clear();
+    residual.extend((order..block.len()).map(|i| {
+        let s = |k: usize| block[i - k] as i32;
+        match order {
+            0 => s(0),
+            1 => s(0) - s(1),
+            2 => s(0) - 2 * s(1) + s(2),
+            3 => s(0) - 3 * s(1) + 3 * s(2) - s(3),
+            _ => s(0) - 4 * s(1) + 6 * s(2) - 4 * s(3) + s(4),
+        }
+    }));
+}
+
+// Partition order with the smallest estimated residual size, and that size in bits
+fn best_partitioning(residual: &[i32], block_size: usize, order: usize) -> (u32, u64) {
+    let mut best = (0, u64::MAX);
+    for partition_order in 0..=MAX_PARTITION_ORDER {
+        let partitions = 1usize << partition_order;
+        if !block_size.is_multiple_of(partitions) || block_size / partitions <= order {
+            break;
+        }
+
+        let mut bits = 6u64; // Coding method + partition order
+        let mut offset = 0;
+        for p in 0..partitions {
+            let len = partition_len(block_size, partition_order, order, p);
+            let sum: u64 = residual[offset..offset + len]
+                .iter()
+                .map(|&r| zigzag(r) as u64)
+                .sum();
+            offset += len;
+            let param = rice_param(sum, len);
+            bits += 4 + len as u64 * (param as u64 + 1) + (sum >> param);
+        }
+
+        if bits < best.1 {
+            best = (partition_order, bits);
+        }
+    }
+    best
+}
+
+fn write_residual(
+    out: &mut BitWriter,
+    residual: &[i32],
+    block_size: usize,
+    order: usize,
+    partition_order: u32,
+) {
+    out.write(0b00, 2); // Rice coding, 4-bit parameters
+    out.write(partition_order as u64, 4);
+
+    let mut offset = 0;
+    for p in 0..1usize << partition_order {
+        let len = partition_len(block_size, partition_order, order, p);
+        let partition = &residual[offset..offset + len];
+        offset += len;
+
+        let sum: u64 = partition.iter().map(|&r| zigzag(r) as u64).sum();
+        let param = rice_param(sum, len);
+        out.write(param as u64, 4);
+        for &r in partition {
+            let value = zigzag(r);
+            out.write_unary(value >> param);
+            out.write((value & ((1 << param) - 1)) as u64, param);
+        }
+    }
+}
+
+// The first partition also holds the warm-up samples, so it codes fewer residuals
+fn partition_len(block_size: usize, partition_order: u32, order: usize, index: usize) -> usize {
+    let len = block_size >> partition_order;
+    if index == 0 {
+        len - order
+    } else {
+        len
+    }
+}
+
+fn rice_param(sum: u64, len: usize) -> u32 {
+    let mean = sum / len.max(1) as u64;
+    (u64::BITS - mean.leading_zeros()).min(MAX_RICE_PARAM)
+}
+
+fn zigzag(value: i32) -> u32 {
+    ((value << 1) ^ (value >> 31)) as u32
+}
+
+// Frame numbers use the UTF-8 style variable-length encoding
+fn write_utf8_number(out: &mut BitWriter, value: u64) {
+    if value < 0x80 {
+        out.write(value, 8);
+        return;
+    }
+
+    // Each continuation byte carries 6 bits; the lead byte loses one per extra byte
+    let mut extra = 1;
+    while value >= 1 << (6 + 5 * extra) {
+        extra += 1;
+    }
+    let marker = (0xFF << (7 - extra)) & 0xFF;
+    out.write(marker | (value >> (6 * extra)), 8);
+    for i in (0..extra).rev() {
+        out.write(0x80 | ((value >> (6 * i)) & 0x3F), 8);
+    }
+}
+
+fn crc8(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0u8, |mut crc, &byte| {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+fn crc16(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(0u16, |mut crc, &byte| {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x8005
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+// MSB-first bit packing
+struct BitWriter {
+    bytes: Vec<u8>,
+    acc: u64,
+    bits: u32, // Pending bits in `acc`
+}
+
+impl BitWriter {
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            bytes: Vec::with_capacity(capacity),
+            acc: 0,
+            bits: 0,
+        }
+    }
+
+    fn write(&mut self, value: u64, bits: u32) {
+        if bits == 0 {
+            return;
+        }
+        let value = value & (u64::MAX >> (64 - bits));
+        for chunk in (0..bits).step_by(32).rev() {
+            let width = (bits - chunk).min(32);
+            self.push((value >> chunk) & ((1 << width) - 1), width);
+        }
+    }
+
+    fn write_signed(&mut self, value: i32, bits: u32) {
+        self.write(value as u32 as u64, bits);
+    }
+
+    fn write_unary(&mut self, zeros: u32) {
+        let mut zeros = zeros;
+        while zeros >= 32 {
+            self.push(0, 32);
+            zeros -= 32;
+        }
+        self.push(1, zeros + 1);
+    }
+
+    // At most 32 bits at a time so `acc` never overflows
+    fn push(&mut self, value: u64, bits: u32) {
+        self.acc = (self.acc << bits) | value;
+        self.bits += bits;
+        while self.bits >= 8 {
+            self.bits -= 8;
+            self.bytes.push((self.acc >> self.bits) as u8);
+        }
+        self.acc &= (1 << self.bits) - 1;
+    }
+
+    fn align(&mut self) {
+        if self.bits > 0 {
+            self.push(0, 8 - self.bits);
+        }
+    }
+
+    fn byte_len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    // Complete bytes written so far
+    fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.write(byte as u64, 8);
+        }
+    }
+
+    fn into_bytes(mut self) -> Vec<u8> {
+        self.align();
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(flac: &[u8]) -> (u32, Vec<i16>) {
+        let mut reader = claxon::FlacReader::new(flac).unwrap();
+        let info = reader.streaminfo();
+        assert_eq!((info.channels, info.bits_per_sample), (1, 16));
+
+        let samples: Vec<i16> = reader
+            .samples()
+            .map(|sample| sample.unwrap() as i16)
+            .collect();
+        // STREAMINFO's total of 0 reads back as "unknown"
+        assert_eq!(info.samples.unwrap_or(0), samples.len() as u64);
+        (info.sample_rate, samples)
+    }
+
+    fn assert_round_trip(samples: &[i16]) {
+        let (sample_rate, decoded) = decode(&encode_flac(samples, 16_000));
+        assert_eq!(sample_rate, 16_000);
+        assert!(decoded == samples, "{} samples differ", samples.len());
+    }
+
+    // Deterministic pseudo-random samples covering the whole i16 range
+    fn noise(len: usize, seed: u32) -> Vec<i16> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (state >> 16) as i16
+            })
+            .collect()
+    }
+
+    fn speech_like(len: usize) -> Vec<i16> {
+        (0..len)
+            .map(|i| {
+                let t = i as f32 / 16_000.0;
+                let s = (t * 220.0 * std::f32::consts::TAU).sin() * 8_000.0
+                    + (t * 1_330.0 * std::f32::consts::TAU).sin() * 2_000.0;
+                s as i16
+            })
+            .collect()
+    }
+
+    #[test]
+    fn empty_input() {
+        assert_round_trip(&[]);
+    }
+
+    #[test]
+    fn single_sample() {
+        assert_round_trip(&[1234]);
+        assert_round_trip(&[i16::MIN]);
+    }
+
+    #[test]
+    fn block_boundaries() {
+        assert_round_trip(&speech_like(BLOCK_SIZE));
+        assert_round_trip(&speech_like(BLOCK_SIZE + 1));
+        assert_round_trip(&speech_like(BLOCK_SIZE * 3 - 7));
+    }
+
+    #[test]
+    fn constant_blocks() {
+        assert_round_trip(&vec![0; BLOCK_SIZE * 2]);
+        assert_round_trip(&vec![-321; BLOCK_SIZE + 5]);
+        assert_round_trip(&[i16::MAX; 10]);
+    }
+
+    #[test]
+    fn full_scale_noise_is_stored_verbatim() {
+        let mut samples = noise(BLOCK_SIZE * 2, 7);
+        samples[0] = i16::MAX;
+        samples[1] = i16::MIN;
+        samples[2] = -i16::MAX;
+        assert_round_trip(&samples);
+
+        // No predictor beats raw 16-bit samples here
+        assert!(encode_flac(&samples, 16_000).len() > samples.len() * 2);
+    }
+
+    #[test]
+    fn frame_numbers_above_127() {
+        // Frame 128 and up need the multi-byte frame number encoding
+        assert_round_trip(&speech_like(BLOCK_SIZE * 130 + 17));
+    }
+}
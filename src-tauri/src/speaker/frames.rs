@@ -0,0 +1,45 @@
+// Meetwings audio frames: fixed-size sample blocks stamped with their capture time
+use futures_util::Stream;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+#[derive(Debug, Clone)]
+pub struct AudioFrame {
+    pub samples: Vec<f32>, // Always `frame_size` long, except the last frame of a stream
+    pub captured_at_ms: u64, // Unix time of the first sample
+}
+
+// Streams that can hand out whole frames instead of single samples
+pub trait FrameSource {
+    fn poll_frame(&mut self, cx: &mut Context<'_>, frame_size: usize) -> Poll<Option<AudioFrame>>;
+
+    fn sample_rate(&self) -> u32;
+}
+
+// Frame-oriented view of a capture stream (see SpeakerStream::frames / MicStream::frames)
+pub struct Frames<S> {
+    source: S,
+    frame_size: usize,
+}
+
+impl<S: FrameSource> Frames<S> {
+    pub fn new(source: S, frame_size: usize) -> Self {
+        Self {
+            source,
+            frame_size: frame_size.max(1),
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.source.sample_rate()
+    }
+}
+
+impl<S: FrameSource + Unpin> Stream for Frames<S> {
+    type Item = AudioFrame;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        this.source.poll_frame(cx, this.frame_size)
+    }
+}
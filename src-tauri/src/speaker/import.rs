@@ -0,0 +1,720 @@
+// Meetwings audio import: decodes recordings from disk (WAV, MP3, M4A/MP4 AAC, Ogg Vorbis,
+// FLAC) to mono at a fixed rate, a packet at a time so long meetings never sit in memory whole,
+// and cuts them into utterances with the same VAD segmentation as live capture
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{Decoder, DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error;
+use symphonia::core::formats::{FormatOptions, FormatReader};
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::mpsc;
+use tracing::{error, warn};
+
+use super::commands::{
+    apply_noise_gate, build_detector, calculate_audio_metrics, normalize_audio_level,
+    samples_to_millis, suppress_noise, NoiseFloor, VadConfig, VadState, VoiceDetector,
+};
+use super::denoise::NoiseSuppressor;
+use super::diagnostics::NoiseEstimate;
+use super::downmix::{downmix, Downmix};
+use super::encode;
+use super::resample::Resampler;
+
+const IMPORT_SAMPLE_RATE: u32 = 16_000; // Imports without a target_sample_rate
+const IMPORT_QUEUE_SIZE: usize = 4; // Utterances decoded ahead of transcription
+const IMPORT_PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+static NEXT_IMPORT_ID: AtomicU64 = AtomicU64::new(1);
+
+pub struct AudioFileDecoder {
+    format: Box<dyn FormatReader>,
+    decoder: Box<dyn Decoder>,
+    track_id: u32,
+    output_rate: u32,
+    resampler: Option<Resampler>, // Created once the first packet reveals the source rate
+    duration_ms: Option<u64>,
+    decoded_frames: u64, // At the source rate
+    source_rate: u32,
+    finished: bool,
+}
+
+impl AudioFileDecoder {
+    pub fn open(path: &Path, output_rate: u32) -> Result<Self, String> {
+        let file =
+            File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+
+        let mut hint = Hint::new();
+        if let Some(extension) = path.extension().and_then(|e| e.to_str()) {
+            hint.with_extension(extension);
+        }
+
+        let probed = symphonia::default::get_probe()
+            .format(
+                &hint,
+                MediaSourceStream::new(Box::new(file), Default::default()),
+                &FormatOptions::default(),
+                &MetadataOptions::default(),
+            )
+            .map_err(|e| format!("Unsupported audio file {}: {}", path.display(), e))?;
+        let format = probed.format;
+
+        // Video files carry other tracks; take the first audio one
+        let track = format
+            .tracks()
+            .iter()
+            .find(|t| t.codec_params.codec != CODEC_TYPE_NULL && t.codec_params.channels.is_some())
+            .or_else(|| {
+                format
+                    .tracks()
+                    .iter()
+                    .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+            })
+            .ok_or_else(|| format!("No audio track in {}", path.display()))?;
+
+        let decoder = symphonia::default::get_codecs()
+            .make(&track.codec_params, &DecoderOptions::default())
+            .map_err(|e| format!("Unsupported audio codec in {}: {}", path.display(), e))?;
+
+        let params = &track.codec_params;
+        let duration_ms = params
+            .n_frames
+            .zip(params.sample_rate)
+            .map(|(frames, rate)| frames * 1000 / rate.max(1) as u64);
+
+        Ok(Self {
+            track_id: track.id,
+            format,
+            decoder,
+            output_rate,
+            resampler: None,
+            duration_ms,
+            decoded_frames: 0,
+            source_rate: 0,
+            finished: false,
+        })
+    }
+
+    // Length of the recording, when the container states it
+    pub fn duration_ms(&self) -> Option<u64> {
+        self.duration_ms
+    }
+
+    // Source audio decoded so far
+    pub fn decoded_ms(&self) -> u64 {
+        self.decoded_frames * 1000 / self.source_rate.max(1) as u64
+    }
+
+    // Next block of mono samples at `output_rate`; None once the file is exhausted
+    pub fn next_block(&mut self) -> Result<Option<Vec<f32>>, String> {
+        if self.finished {
+            return Ok(None);
+        }
+
+        loop {
+            let packet = match self.format.next_packet() {
+                Ok(packet) => packet,
+                // Symphonia reports the end of the stream as an unexpected EOF
+                Err(Error::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                    self.finished = true;
+                    return Ok(self
+                        .resampler
+                        .as_mut()
+                        .map(|resampler| resampler.flush())
+                        .filter(|tail| !tail.is_empty()));
+                }
+                Err(e) => return Err(format!("Failed to read audio file: {}", e)),
+            };
+
+            if packet.track_id() != self.track_id {
+                continue;
+            }
+
+            let decoded = match self.decoder.decode(&packet) {
+                Ok(decoded) => decoded,
+                // A corrupt packet costs a few milliseconds of audio, not the import
+                Err(Error::DecodeError(e)) => {
+                    warn!("Skipping undecodable audio packet: {}", e);
+                    continue;
+                }
+                Err(e) => return Err(format!("Failed to decode audio file: {}", e)),
+            };
+
+            let spec = *decoded.spec();
+            let channels = spec.channels.count().max(1);
+            let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+            buffer.copy_interleaved_ref(decoded);
+
+            let mono = downmix(buffer.samples(), channels, Downmix::Average);
+            self.decoded_frames += mono.len() as u64;
+            self.source_rate = spec.rate;
+
+            let output_rate = self.output_rate;
+            let resampler = self
+                .resampler
+                .get_or_insert_with(|| Resampler::new(spec.rate, output_rate));
+            let block = resampler.process(&mono);
+            if !block.is_empty() {
+                return Ok(Some(block));
+            }
+        }
+    }
+}
+
+// `audio-import-started` / `audio-import-progress` payload. Decoding runs a few
+// utterances ahead of transcription, so both positions are reported.
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioImportProgress {
+    pub import_id: u64, // Pass to cancel_audio_import
+    pub decoded_ms: u64,
+    pub duration_ms: Option<u64>, // None when the container doesn't state it
+    pub segments_found: u32,
+    pub segments_transcribed: u32,
+}
+
+// `audio-import-segment` payload, in file order
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportedSegment {
+    pub import_id: u64,
+    pub index: u32,
+    pub start_ms: u64, // Offset into the file
+    pub duration_ms: u64,
+    pub transcription: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioImportStatus {
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+// `audio-import-finished` payload
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioImportResult {
+    pub import_id: u64,
+    pub status: AudioImportStatus,
+    pub segments_transcribed: u32,
+    pub error: Option<String>,
+}
+
+// Counters shared by the decoding thread and the transcription task
+struct ImportProgress {
+    import_id: u64,
+    duration_ms: Option<u64>,
+    decoded_ms: AtomicU64,
+    segments_found: AtomicU32,
+    segments_transcribed: AtomicU32,
+}
+
+impl ImportProgress {
+    fn snapshot(&self) -> AudioImportProgress {
+        AudioImportProgress {
+            import_id: self.import_id,
+            decoded_ms: self.decoded_ms.load(Ordering::Relaxed),
+            duration_ms: self.duration_ms,
+            segments_found: self.segments_found.load(Ordering::Relaxed),
+            segments_transcribed: self.segments_transcribed.load(Ordering::Relaxed),
+        }
+    }
+}
+
+// An utterance cut by UtteranceSplitter
+pub(super) struct Utterance {
+    pub start: usize,      // Sample offset of its first (pre-speech) sample
+    pub samples: Vec<f32>, // Noise-gated, trailing silence trimmed to ~0.15s
+}
+
+// What one hop did to the utterance being collected
+pub(super) enum SplitEvent {
+    Continue,
+    Started { lead_in: usize }, // Pre-speech samples buffered ahead of this hop
+    Completed(Utterance),
+    Discarded(Utterance), // Ended with fewer than min_speech_chunks of speech
+}
+
+pub(super) struct Hop {
+    pub event: SplitEvent,
+    pub rms: f32, // Of the noise-gated hop
+    pub peak: f32,
+    pub thresholds: NoiseEstimate,
+}
+
+// VAD segmentation shared by live capture (run_vad_capture) and file import: detector,
+// noise floor and gate, pre-speech buffer, 30s cap, trailing-silence trim and the
+// min_speech_chunks rule. Also owns the optional noise suppressor, which learns from the
+// hops between utterances.
+pub(super) struct UtteranceSplitter {
+    config: VadConfig,
+    sample_rate: u32,
+    detector: Box<dyn VoiceDetector>,
+    noise_floor: NoiseFloor,
+    suppressor: Option<NoiseSuppressor>,
+    pre_speech: VecDeque<f32>,
+    speech_buffer: Vec<f32>,
+    speech_start: usize,
+    position: usize, // Samples pushed so far
+    in_speech: bool,
+    silence_chunks: usize,
+    speech_chunks: usize,
+}
+
+impl UtteranceSplitter {
+    pub fn new(config: VadConfig, sample_rate: u32) -> Self {
+        Self {
+            detector: build_detector(&config, sample_rate),
+            noise_floor: NoiseFloor::new(),
+            suppressor: config.noise_suppression.then(|| {
+                NoiseSuppressor::new(
+                    sample_rate,
+                    config.noise_suppression_strength,
+                    config.noise_suppression_floor_db,
+                )
+            }),
+            pre_speech: VecDeque::with_capacity(config.pre_speech_chunks * config.hop_size),
+            speech_buffer: Vec::new(),
+            speech_start: 0,
+            position: 0,
+            in_speech: false,
+            silence_chunks: 0,
+            speech_chunks: 0,
+            config,
+            sample_rate,
+        }
+    }
+
+    // Takes one hop of raw audio
+    pub fn push(&mut self, chunk: &[f32]) -> Hop {
+        let thresholds = self.noise_floor.thresholds(&self.config);
+        self.detector
+            .set_thresholds(thresholds.sensitivity_rms, thresholds.peak_threshold);
+
+        // Apply noise gate BEFORE VAD (critical for accuracy)
+        let mono = apply_noise_gate(chunk, thresholds.noise_gate_threshold);
+        let (rms, peak) = calculate_audio_metrics(&mono);
+        let is_speech = self.detector.is_speech(&mono, rms, peak);
+
+        let (raw_rms, _) = calculate_audio_metrics(chunk);
+        self.noise_floor.update(raw_rms, is_speech);
+        let chunk_start = self.position;
+        self.position += chunk.len();
+
+        let mut event = SplitEvent::Continue;
+        if is_speech {
+            if !self.in_speech {
+                // Include pre-speech buffer for natural sound
+                self.in_speech = true;
+                self.speech_buffer.extend(self.pre_speech.drain(..));
+                self.speech_start = chunk_start - self.speech_buffer.len();
+                event = SplitEvent::Started {
+                    lead_in: self.speech_buffer.len(),
+                };
+            }
+            self.speech_chunks += 1;
+            self.silence_chunks = 0;
+            self.speech_buffer.extend_from_slice(&mono);
+
+            // Safety cap: 30s per utterance
+            if self.speech_buffer.len() > self.sample_rate as usize * 30 {
+                event = self.take();
+            }
+        } else if self.in_speech {
+            // Continue collecting during silence (important for natural speech)
+            self.silence_chunks += 1;
+            self.speech_buffer.extend_from_slice(&mono);
+
+            if self.silence_chunks >= self.config.silence_chunks {
+                // Trim trailing silence (keep ~0.15s for natural ending)
+                let trim_amount = (self.silence_chunks * self.config.hop_size)
+                    .saturating_sub(self.sample_rate as usize * 15 / 100);
+                let keep = self.speech_buffer.len().saturating_sub(trim_amount);
+                self.speech_buffer.truncate(keep);
+                event = self.take();
+            }
+        } else {
+            if let Some(suppressor) = self.suppressor.as_mut() {
+                suppressor.learn(&mono);
+            }
+            self.pre_speech.extend(mono);
+            while self.pre_speech.len() > self.config.pre_speech_chunks * self.config.hop_size {
+                self.pre_speech.pop_front();
+            }
+        }
+
+        Hop {
+            event,
+            rms,
+            peak,
+            thresholds,
+        }
+    }
+
+    // Closes the utterance still open when the audio ends; None if it was too short
+    pub fn finish(&mut self) -> Option<Utterance> {
+        if !self.in_speech {
+            return None;
+        }
+        match self.take() {
+            SplitEvent::Completed(utterance) => Some(utterance),
+            _ => None,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.in_speech
+    }
+
+    // Samples collected for the open utterance, pre-speech included
+    pub fn speech_len(&self) -> usize {
+        self.speech_buffer.len()
+    }
+
+    pub fn noise_floor(&self) -> f32 {
+        self.noise_floor.level()
+    }
+
+    pub fn vad_state(&self) -> VadState {
+        match (self.in_speech, self.silence_chunks) {
+            (false, _) => VadState::Silence,
+            (true, 0) => VadState::Speech,
+            (true, _) => VadState::Hangover,
+        }
+    }
+
+    // Noise suppression (when enabled) and level normalization for emitted audio
+    pub fn clean(&mut self, samples: &[f32]) -> Vec<f32> {
+        normalize_audio_level(&suppress_noise(&mut self.suppressor, samples), 0.1)
+    }
+
+    // clean() of the open utterance from sample `from` on
+    pub fn clean_speech(&mut self, from: usize) -> Vec<f32> {
+        let speech = &self.speech_buffer[from.min(self.speech_buffer.len())..];
+        normalize_audio_level(&suppress_noise(&mut self.suppressor, speech), 0.1)
+    }
+
+    fn take(&mut self) -> SplitEvent {
+        let long_enough = self.speech_chunks >= self.config.min_speech_chunks;
+        let utterance = Utterance {
+            start: self.speech_start,
+            samples: std::mem::take(&mut self.speech_buffer),
+        };
+        self.in_speech = false;
+        self.silence_chunks = 0;
+        self.speech_chunks = 0;
+
+        if long_enough && !utterance.samples.is_empty() {
+            SplitEvent::Completed(utterance)
+        } else {
+            SplitEvent::Discarded(utterance)
+        }
+    }
+}
+
+// Decodes the file and splits it into utterances on a blocking thread. Stops early
+// when the import is cancelled or the transcription side hangs up.
+fn split_audio_file(
+    app: &AppHandle,
+    mut decoder: AudioFileDecoder,
+    config: VadConfig,
+    sample_rate: u32,
+    cancel: &AtomicBool,
+    progress: &ImportProgress,
+    utterances: mpsc::Sender<Result<Utterance, String>>,
+) {
+    let hop_size = config.hop_size.max(1);
+    let mut splitter = UtteranceSplitter::new(config, sample_rate);
+    let mut pending: Vec<f32> = Vec::with_capacity(hop_size * 2);
+    let mut last_progress = Instant::now();
+
+    let send = |splitter: &mut UtteranceSplitter, utterance: Utterance| {
+        progress.segments_found.fetch_add(1, Ordering::Relaxed);
+        let samples = splitter.clean(&utterance.samples);
+        utterances
+            .blocking_send(Ok(Utterance {
+                samples,
+                ..utterance
+            }))
+            .is_ok()
+    };
+
+    loop {
+        if cancel.load(Ordering::Acquire) {
+            return;
+        }
+
+        let block = match decoder.next_block() {
+            Ok(Some(block)) => block,
+            Ok(None) => break,
+            Err(e) => {
+                let _ = utterances.blocking_send(Err(e));
+                return;
+            }
+        };
+
+        pending.extend_from_slice(&block);
+        let mut hops = pending.chunks_exact(hop_size);
+        for hop in hops.by_ref() {
+            if let SplitEvent::Completed(utterance) = splitter.push(hop).event {
+                if !send(&mut splitter, utterance) {
+                    return;
+                }
+            }
+        }
+        let consumed = pending.len() - hops.remainder().len();
+        pending.drain(..consumed);
+
+        progress
+            .decoded_ms
+            .store(decoder.decoded_ms(), Ordering::Relaxed);
+        if last_progress.elapsed() >= IMPORT_PROGRESS_INTERVAL {
+            let _ = app.emit("audio-import-progress", progress.snapshot());
+            last_progress = Instant::now();
+        }
+    }
+
+    // The last partial hop, then whatever utterance is still open
+    let mut tail = Vec::new();
+    if !pending.is_empty() {
+        if let SplitEvent::Completed(utterance) = splitter.push(&pending).event {
+            tail.push(utterance);
+        }
+    }
+    tail.extend(splitter.finish());
+    for utterance in tail {
+        if !send(&mut splitter, utterance) {
+            return;
+        }
+    }
+    progress
+        .decoded_ms
+        .store(decoder.decoded_ms(), Ordering::Relaxed);
+}
+
+// Transcribes utterances as the decoding thread produces them, in file order
+async fn run_audio_import(
+    app: &AppHandle,
+    decoder: AudioFileDecoder,
+    config: VadConfig,
+    sample_rate: u32,
+    cancel: Arc<AtomicBool>,
+    progress: Arc<ImportProgress>,
+) -> Result<(), String> {
+    let (sender, mut utterances) = mpsc::channel(IMPORT_QUEUE_SIZE);
+    let encoding = config.encoding;
+
+    let splitting = tokio::task::spawn_blocking({
+        let app = app.clone();
+        let cancel = cancel.clone();
+        let progress = progress.clone();
+        move || {
+            split_audio_file(
+                &app,
+                decoder,
+                config,
+                sample_rate,
+                &cancel,
+                &progress,
+                sender,
+            )
+        }
+    });
+
+    // Cancellation takes effect between segments; a request already sent is awaited
+    while let Some(utterance) = utterances.recv().await {
+        if cancel.load(Ordering::Acquire) {
+            break;
+        }
+        let utterance = utterance?;
+
+        let audio = encode::encode(encoding, sample_rate, &utterance.samples)?;
+        let transcription = crate::api::transcribe_audio_bytes(app, &audio).await?;
+
+        let index = progress
+            .segments_transcribed
+            .fetch_add(1, Ordering::Relaxed);
+        let transcription = transcription.trim();
+        if !transcription.is_empty() {
+            let _ = app.emit(
+                "audio-import-segment",
+                ImportedSegment {
+                    import_id: progress.import_id,
+                    index,
+                    start_ms: samples_to_millis(utterance.start, sample_rate),
+                    duration_ms: samples_to_millis(utterance.samples.len(), sample_rate),
+                    transcription: transcription.to_string(),
+                },
+            );
+        }
+        let _ = app.emit("audio-import-progress", progress.snapshot());
+    }
+
+    // Dropping the receiver unblocks the decoding thread if it is still running
+    drop(utterances);
+    let _ = splitting.await;
+    Ok(())
+}
+
+// Probes the file and runs the import in the background (see import_audio_file)
+pub async fn start_import(app: AppHandle, path: String, config: VadConfig) -> Result<u64, String> {
+    let state = app.state::<crate::AudioState>();
+    let sample_rate = config.target_sample_rate.unwrap_or(IMPORT_SAMPLE_RATE);
+
+    // Probing reads the container header, so unsupported files fail here rather than mid-import
+    let decoder =
+        tokio::task::spawn_blocking(move || AudioFileDecoder::open(Path::new(&path), sample_rate))
+            .await
+            .map_err(|e| format!("Audio import task failed: {}", e))??;
+
+    let import_id = NEXT_IMPORT_ID.fetch_add(1, Ordering::Relaxed);
+    let cancel = Arc::new(AtomicBool::new(false));
+    state
+        .audio_imports
+        .lock()
+        .map_err(|e| format!("Failed to register audio import: {}", e))?
+        .insert(import_id, cancel.clone());
+
+    let progress = Arc::new(ImportProgress {
+        import_id,
+        duration_ms: decoder.duration_ms(),
+        decoded_ms: AtomicU64::new(0),
+        segments_found: AtomicU32::new(0),
+        segments_transcribed: AtomicU32::new(0),
+    });
+    let _ = app.emit("audio-import-started", progress.snapshot());
+
+    let app_clone = app.clone();
+    tokio::spawn(async move {
+        let result = run_audio_import(
+            &app_clone,
+            decoder,
+            config,
+            sample_rate,
+            cancel.clone(),
+            progress.clone(),
+        )
+        .await;
+
+        if let Ok(mut imports) = app_clone.state::<crate::AudioState>().audio_imports.lock() {
+            imports.remove(&import_id);
+        }
+
+        let (status, error) = match result {
+            Ok(()) if cancel.load(Ordering::Acquire) => (AudioImportStatus::Cancelled, None),
+            Ok(()) => (AudioImportStatus::Completed, None),
+            Err(e) => {
+                error!("Audio import {} failed: {}", import_id, e);
+                (AudioImportStatus::Failed, Some(e))
+            }
+        };
+        let _ = app_clone.emit(
+            "audio-import-finished",
+            AudioImportResult {
+                import_id,
+                status,
+                segments_transcribed: progress.segments_transcribed.load(Ordering::Relaxed),
+                error,
+            },
+        );
+    });
+
+    Ok(import_id)
+}
+
+pub fn cancel_import(app: &AppHandle, import_id: u64) -> Result<(), String> {
+    let state = app.state::<crate::AudioState>();
+    let imports = state
+        .audio_imports
+        .lock()
+        .map_err(|e| format!("Failed to read audio imports: {}", e))?;
+
+    match imports.get(&import_id) {
+        Some(cancel) => {
+            cancel.store(true, Ordering::Release);
+            Ok(())
+        }
+        None => Err(format!("No running audio import with ID {}", import_id)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16_000;
+
+    fn tone(ms: usize) -> Vec<f32> {
+        (0..RATE as usize * ms / 1000)
+            .map(|i| 0.3 * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / RATE as f32).sin())
+            .collect()
+    }
+
+    fn silence(ms: usize) -> Vec<f32> {
+        vec![0.0; RATE as usize * ms / 1000]
+    }
+
+    fn split(config: VadConfig, audio: &[f32]) -> Vec<Utterance> {
+        let mut splitter = UtteranceSplitter::new(config.clone(), RATE);
+        let mut utterances = Vec::new();
+        for hop in audio.chunks(config.hop_size) {
+            if let SplitEvent::Completed(utterance) = splitter.push(hop).event {
+                utterances.push(utterance);
+            }
+        }
+        utterances.extend(splitter.finish());
+        utterances
+    }
+
+    #[test]
+    fn pause_splits_two_utterances() {
+        let config = VadConfig::default();
+        let audio = [
+            silence(1000),
+            tone(1000),
+            silence(4000),
+            tone(1000),
+            silence(4000),
+        ]
+        .concat();
+
+        let utterances = split(config.clone(), &audio);
+        assert_eq!(utterances.len(), 2);
+
+        // Each starts up to pre_speech_chunks (+ the partial first hop) ahead of its tone
+        // and ends ~0.15s after it
+        let lead_in = (config.pre_speech_chunks + 1) * config.hop_size;
+        let tail = RATE as usize * 15 / 100 + config.hop_size;
+        for (utterance, tone_start) in utterances.iter().zip([RATE as usize, RATE as usize * 6]) {
+            let tone_end = tone_start + RATE as usize;
+            let end = utterance.start + utterance.samples.len();
+            assert!(utterance.start <= tone_start && tone_start - utterance.start <= lead_in);
+            assert!(end >= tone_end && end <= tone_end + tail);
+        }
+    }
+
+    #[test]
+    fn blip_shorter_than_min_speech_is_discarded() {
+        let config = VadConfig::default();
+        let audio = [silence(1000), tone(50), silence(4000)].concat();
+
+        let mut splitter = UtteranceSplitter::new(config.clone(), RATE);
+        let mut discarded = 0;
+        for hop in audio.chunks(config.hop_size) {
+            match splitter.push(hop).event {
+                SplitEvent::Completed(_) => panic!("blip was kept"),
+                SplitEvent::Discarded(_) => discarded += 1,
+                _ => {}
+            }
+        }
+        assert_eq!(discarded, 1);
+        assert!(splitter.finish().is_none());
+    }
+}
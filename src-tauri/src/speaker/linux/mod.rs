@@ -0,0 +1,173 @@
+// Meetwings linux speaker input and stream: native PipeWire when its server is running
+// (builds with the `pipewire` feature), PulseAudio otherwise
+use anyhow::Result;
+use futures_util::Stream;
+use std::pin::Pin;
+use std::task::Poll;
+
+use crate::speaker::downmix::ChannelMode;
+use crate::speaker::frames::AudioFrame;
+use crate::speaker::queue::QueueHandle;
+use crate::speaker::{AudioDevice, DeviceEventCallback};
+
+#[cfg(feature = "pipewire")]
+mod pipewire;
+mod pulseaudio;
+
+// Set to "pulseaudio" or "pipewire" to skip backend detection
+#[cfg(feature = "pipewire")]
+const BACKEND_ENV: &str = "MEETWINGS_AUDIO_BACKEND";
+
+pub struct SpeakerInput {
+    inner: InputBackend,
+}
+
+enum InputBackend {
+    #[cfg(feature = "pipewire")]
+    PipeWire(pipewire::SpeakerInput),
+    PulseAudio(pulseaudio::SpeakerInput),
+}
+
+impl SpeakerInput {
+    pub fn new(device_id: Option<String>) -> Result<Self> {
+        #[cfg(feature = "pipewire")]
+        if use_pipewire(device_id.as_deref()) {
+            let inner = InputBackend::PipeWire(pipewire::SpeakerInput::new(device_id)?);
+            return Ok(Self { inner });
+        }
+
+        let inner = InputBackend::PulseAudio(pulseaudio::SpeakerInput::new(device_id)?);
+        Ok(Self { inner })
+    }
+
+    pub fn set_device_event_callback(&mut self, callback: DeviceEventCallback) {
+        match &mut self.inner {
+            #[cfg(feature = "pipewire")]
+            InputBackend::PipeWire(input) => input.set_device_event_callback(callback),
+            InputBackend::PulseAudio(input) => input.set_device_event_callback(callback),
+        }
+    }
+
+    pub fn set_channel_mode(&mut self, mode: ChannelMode) {
+        match &mut self.inner {
+            #[cfg(feature = "pipewire")]
+            InputBackend::PipeWire(input) => input.set_channel_mode(mode),
+            InputBackend::PulseAudio(input) => input.set_channel_mode(mode),
+        }
+    }
+
+    // PulseAudio's list (also served by pipewire-pulse) includes applications; PipeWire's
+    // own registry is the fallback when no pulse server answers. Both use the same IDs.
+    pub fn list_devices() -> Result<Vec<AudioDevice>> {
+        let listed = pulseaudio::SpeakerInput::list_devices();
+
+        #[cfg(feature = "pipewire")]
+        if let Err(e) = &listed {
+            if pipewire::is_available() {
+                eprintln!("PulseAudio device list unavailable, asking PipeWire: {}", e);
+                return pipewire::SpeakerInput::list_devices();
+            }
+        }
+
+        listed
+    }
+
+    pub fn stream(self) -> SpeakerStream {
+        let inner = match self.inner {
+            #[cfg(feature = "pipewire")]
+            InputBackend::PipeWire(input) => StreamBackend::PipeWire(input.stream()),
+            InputBackend::PulseAudio(input) => StreamBackend::PulseAudio(input.stream()),
+        };
+        SpeakerStream { inner }
+    }
+}
+
+pub struct SpeakerStream {
+    inner: StreamBackend,
+}
+
+enum StreamBackend {
+    #[cfg(feature = "pipewire")]
+    PipeWire(pipewire::SpeakerStream),
+    PulseAudio(pulseaudio::SpeakerStream),
+}
+
+impl SpeakerStream {
+    pub fn sample_rate(&self) -> u32 {
+        match &self.inner {
+            #[cfg(feature = "pipewire")]
+            StreamBackend::PipeWire(stream) => stream.sample_rate(),
+            StreamBackend::PulseAudio(stream) => stream.sample_rate(),
+        }
+    }
+
+    pub fn backend(&self) -> &'static str {
+        match &self.inner {
+            #[cfg(feature = "pipewire")]
+            StreamBackend::PipeWire(stream) => stream.backend(),
+            StreamBackend::PulseAudio(stream) => stream.backend(),
+        }
+    }
+
+    pub fn channels(&self) -> usize {
+        match &self.inner {
+            #[cfg(feature = "pipewire")]
+            StreamBackend::PipeWire(stream) => stream.channels(),
+            StreamBackend::PulseAudio(stream) => stream.channels(),
+        }
+    }
+
+    pub fn queue(&self) -> &QueueHandle {
+        match &self.inner {
+            #[cfg(feature = "pipewire")]
+            StreamBackend::PipeWire(stream) => stream.queue(),
+            StreamBackend::PulseAudio(stream) => stream.queue(),
+        }
+    }
+
+    pub fn poll_frame(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+        frame_size: usize,
+    ) -> Poll<Option<AudioFrame>> {
+        match &mut self.inner {
+            #[cfg(feature = "pipewire")]
+            StreamBackend::PipeWire(stream) => stream.poll_frame(cx, frame_size),
+            StreamBackend::PulseAudio(stream) => stream.poll_frame(cx, frame_size),
+        }
+    }
+}
+
+impl Stream for SpeakerStream {
+    type Item = f32;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        match &mut self.inner {
+            #[cfg(feature = "pipewire")]
+            StreamBackend::PipeWire(stream) => Pin::new(stream).poll_next(cx),
+            StreamBackend::PulseAudio(stream) => Pin::new(stream).poll_next(cx),
+        }
+    }
+}
+
+// Removes per-application capture sinks left behind by a run that crashed
+pub fn unload_stale_routes() -> Result<()> {
+    pulseaudio::unload_stale_routes()
+}
+
+#[cfg(feature = "pipewire")]
+fn use_pipewire(device_id: Option<&str>) -> bool {
+    // Per-application capture reroutes streams with PulseAudio modules
+    if device_id.is_some_and(|id| id.starts_with(pulseaudio::APP_DEVICE_PREFIX)) {
+        return false;
+    }
+
+    match std::env::var(BACKEND_ENV).as_deref() {
+        Ok("pulseaudio") => false,
+        Ok("pipewire") => true,
+        _ => pipewire::is_available(),
+    }
+}
@@ -0,0 +1,717 @@
+// Meetwings linux speaker input and stream on PipeWire's native API. The session manager
+// links the stream, so default sink changes and unplugged targets need no reopening here.
+use ::pipewire as pw;
+use anyhow::{anyhow, Result};
+use futures_util::Stream;
+use std::cell::{Cell, RefCell};
+use std::io::Cursor;
+use std::rc::Rc;
+use std::sync::mpsc;
+use std::task::Poll;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use pw::properties::properties;
+use pw::spa;
+use pw::stream::{Stream as PwStream, StreamFlags, StreamState};
+use pw::types::ObjectType;
+use spa::param::audio::{AudioFormat, AudioInfoRaw};
+use spa::param::format::{MediaSubtype, MediaType};
+use spa::param::{format_utils, ParamType};
+use spa::pod::serialize::PodSerializer;
+use spa::pod::{Object, Pod, Value};
+use spa::utils::{Direction, SpaTypes};
+
+use crate::speaker::downmix::ChannelMode;
+use crate::speaker::frames::AudioFrame;
+use crate::speaker::queue::{
+    sample_queue, QueueHandle, SampleConsumer, SampleProducer, QUEUE_CAPACITY,
+};
+use crate::speaker::{AudioDevice, AudioDeviceKind, DeviceEvent, DeviceEventCallback};
+
+const DEFAULT_SAMPLE_RATE: u32 = 48_000; // PipeWire's default graph rate
+const DEFAULT_MONITOR: &str = "@DEFAULT_MONITOR@"; // Reported source while following the default sink
+const MONITOR_SUFFIX: &str = ".monitor";
+const CLOSE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+const LINK_TIMEOUT: Duration = Duration::from_secs(3);
+const DONT_RECONNECT: &str = "node.dont-reconnect"; // Fail instead of moving to another device
+const DEFAULT_METADATA: &str = "default"; // Session manager metadata holding the defaults
+const DEFAULT_SINK_KEY: &str = "default.audio.sink";
+
+type InitSender = mpsc::Sender<Result<(u32, usize)>>;
+
+// Whether a PipeWire server accepts connections. Probed on every call, so a server that
+// starts after the app (or goes away) is picked up by the next capture.
+pub fn is_available() -> bool {
+    Connection::open().is_ok()
+}
+
+pub struct SpeakerInput {
+    target: Option<String>,
+    on_device_event: Option<DeviceEventCallback>,
+    channel_mode: ChannelMode,
+}
+
+impl SpeakerInput {
+    pub fn new(device_id: Option<String>) -> Result<Self> {
+        // Same IDs as the PulseAudio backend: `<sink>.monitor` or a source node name
+        Ok(Self {
+            target: device_id,
+            on_device_event: None,
+            channel_mode: ChannelMode::default(),
+        })
+    }
+
+    pub fn set_device_event_callback(&mut self, callback: DeviceEventCallback) {
+        self.on_device_event = Some(callback);
+    }
+
+    pub fn set_channel_mode(&mut self, mode: ChannelMode) {
+        self.channel_mode = mode;
+    }
+
+    // Lists sinks from the registry as monitor devices
+    pub fn list_devices() -> Result<Vec<AudioDevice>> {
+        let connection = Connection::open()?;
+        let registry = connection
+            .core
+            .get_registry()
+            .map_err(|e| anyhow!("Failed to get PipeWire registry: {}", e))?;
+
+        let devices = Rc::new(RefCell::new(Vec::new()));
+        let devices_clone = devices.clone();
+        let _listener = registry
+            .add_listener_local()
+            .global(move |global| {
+                if global.type_ != ObjectType::Node {
+                    return;
+                }
+                let Some(props) = global.props else {
+                    return;
+                };
+                if props.get(*pw::keys::MEDIA_CLASS) != Some("Audio/Sink") {
+                    return;
+                }
+                let Some(node_name) = props.get(*pw::keys::NODE_NAME) else {
+                    return;
+                };
+
+                devices_clone.borrow_mut().push(AudioDevice {
+                    id: format!("{}{}", node_name, MONITOR_SUFFIX),
+                    name: props
+                        .get(*pw::keys::NODE_DESCRIPTION)
+                        .unwrap_or(node_name)
+                        .to_string(),
+                    kind: AudioDeviceKind::Monitor,
+                    is_default: false, // Lives in the session manager's metadata, not the node
+                    channels: props
+                        .get(*pw::keys::AUDIO_CHANNELS)
+                        .and_then(|c| c.parse().ok())
+                        .unwrap_or(2),
+                    sample_rate: props
+                        .get(*pw::keys::AUDIO_RATE)
+                        .and_then(|r| r.parse().ok())
+                        .unwrap_or(DEFAULT_SAMPLE_RATE),
+                });
+            })
+            .register();
+        connection.roundtrip()?;
+
+        let devices = devices.take();
+        Ok(devices)
+    }
+
+    pub fn stream(self) -> SpeakerStream {
+        let (producer, consumer) = sample_queue(QUEUE_CAPACITY);
+        let (init_tx, init_rx) = mpsc::channel();
+
+        let target = self.target;
+        let on_device_event = self.on_device_event;
+        let channel_mode = self.channel_mode;
+
+        let mut capture_thread = Some(thread::spawn(move || {
+            SpeakerStream::capture_audio_loop(
+                producer,
+                target.as_deref(),
+                init_tx,
+                on_device_event,
+                channel_mode,
+            )
+        }));
+
+        let (sample_rate, channels, init_success) = match init_rx.recv() {
+            Ok(Ok((sr, channels))) => (sr, channels, true),
+            Ok(Err(e)) => {
+                eprintln!("Audio initialization failed: {}", e);
+                consumer.handle().record_error(&e);
+                (DEFAULT_SAMPLE_RATE, 1, false)
+            }
+            Err(e) => {
+                eprintln!("Failed to receive audio init signal: {}", e);
+                (DEFAULT_SAMPLE_RATE, 1, false)
+            }
+        };
+
+        if !init_success {
+            consumer.handle().close();
+
+            if let Some(handle) = capture_thread.take() {
+                let _ = handle.join();
+            }
+        }
+
+        SpeakerStream {
+            queue: consumer,
+            capture_thread,
+            sample_rate,
+            channels,
+        }
+    }
+}
+
+pub struct SpeakerStream {
+    queue: SampleConsumer,
+    capture_thread: Option<thread::JoinHandle<()>>,
+    sample_rate: u32,
+    channels: usize,
+}
+
+impl SpeakerStream {
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn backend(&self) -> &'static str {
+        "pipewire"
+    }
+
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    pub fn queue(&self) -> &QueueHandle {
+        self.queue.handle()
+    }
+
+    pub fn poll_frame(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+        frame_size: usize,
+    ) -> Poll<Option<AudioFrame>> {
+        self.queue.poll_frame(cx, frame_size, self.sample_rate)
+    }
+
+    fn capture_audio_loop(
+        producer: SampleProducer,
+        target: Option<&str>,
+        init_tx: InitSender,
+        on_device_event: Option<DeviceEventCallback>,
+        channel_mode: ChannelMode,
+    ) {
+        let queue = producer.handle().clone();
+        let init = Rc::new(RefCell::new(Some(init_tx)));
+
+        if let Err(e) = run_capture(
+            producer,
+            target,
+            init.clone(),
+            on_device_event,
+            channel_mode,
+        ) {
+            // Before the format is known the error belongs to stream(); after, to the queue
+            match init.borrow_mut().take() {
+                Some(init_tx) => {
+                    let _ = init_tx.send(Err(e));
+                }
+                None => {
+                    eprintln!("PipeWire capture failed: {}", e);
+                    queue.record_error(&e);
+                }
+            }
+        }
+
+        // End the stream so consumers see the capture finish
+        queue.close();
+    }
+}
+
+// Listener state for the capture stream
+struct CaptureState {
+    producer: SampleProducer,
+    format: AudioInfoRaw,
+    negotiated: Option<AudioInfoRaw>, // First format; later ones are converted back to it
+    channel_mode: ChannelMode,
+    samples: Vec<f32>, // Scratch buffers reused by every process callback
+    mixed: Vec<f32>,
+    source: Rc<RefCell<String>>, // Updated by DefaultSinkWatcher when following the default
+    init: Rc<RefCell<Option<InitSender>>>,
+}
+
+// Runs the capture stream on this thread until the queue closes or the stream fails
+fn run_capture(
+    producer: SampleProducer,
+    target: Option<&str>,
+    init: Rc<RefCell<Option<InitSender>>>,
+    on_device_event: Option<DeviceEventCallback>,
+    channel_mode: ChannelMode,
+) -> Result<()> {
+    let connection = Connection::open()?;
+    let queue = producer.handle().clone();
+
+    let mut props = properties! {
+        *pw::keys::MEDIA_TYPE => "Audio",
+        *pw::keys::MEDIA_CATEGORY => "Capture",
+        *pw::keys::MEDIA_ROLE => "Communication",
+        *pw::keys::APP_NAME => "Meetwings",
+    };
+    for (key, value) in target_properties(target) {
+        props.insert(key, value);
+    }
+    let source = Rc::new(RefCell::new(target.unwrap_or(DEFAULT_MONITOR).to_string()));
+
+    // The session manager relinks a default-following stream by itself; watch the
+    // default sink only to report the switch
+    let _watcher = match target {
+        Some(_) => None,
+        None => Some(DefaultSinkWatcher::new(
+            &connection,
+            source.clone(),
+            queue.clone(),
+            on_device_event.clone(),
+        )?),
+    };
+
+    let stream = PwStream::new(&connection.core, "System Audio Capture", props)
+        .map_err(|e| anyhow!("Failed to create PipeWire stream: {}", e))?;
+
+    let mainloop = connection.mainloop.clone();
+    let state = CaptureState {
+        producer,
+        format: AudioInfoRaw::new(),
+        negotiated: None,
+        channel_mode,
+        samples: Vec::new(),
+        mixed: Vec::new(),
+        source: source.clone(),
+        init: init.clone(),
+    };
+    let _listener = stream
+        .add_local_listener_with_user_data(state)
+        .param_changed(|stream, state, id, param| {
+            let Some(param) = param else {
+                return;
+            };
+            if id != ParamType::Format.as_raw() {
+                return;
+            }
+            match format_utils::parse_format(param) {
+                Ok((MediaType::Audio, MediaSubtype::Raw)) => {}
+                _ => return,
+            }
+            if state.format.parse(param).is_err() {
+                return;
+            }
+
+            match state.negotiated {
+                None => {
+                    state.negotiated = Some(state.format);
+                    state.producer.handle().set_source(&state.source.borrow());
+                    let channels = state.format.channels() as usize;
+                    if let Some(init_tx) = state.init.borrow_mut().take() {
+                        let _ = init_tx.send(Ok((
+                            state.format.rate(),
+                            state.channel_mode.output_channels(channels),
+                        )));
+                    }
+                }
+                // Consumers assume one rate and layout per stream; have PipeWire convert
+                Some(negotiated)
+                    if negotiated.rate() != state.format.rate()
+                        || negotiated.channels() != state.format.channels() =>
+                {
+                    let pinned = format_param(negotiated).and_then(|bytes| {
+                        let pod = Pod::from_bytes(&bytes)
+                            .ok_or_else(|| anyhow!("Invalid PipeWire format"))?;
+                        stream
+                            .update_params(&mut [pod])
+                            .map_err(|e| anyhow!("Failed to keep capture format: {}", e))
+                    });
+                    if let Err(e) = pinned {
+                        eprintln!("{}", e);
+                        state.producer.handle().record_error(&e);
+                    }
+                }
+                Some(_) => {}
+            }
+        })
+        .process(|stream, state| {
+            let Some(mut buffer) = stream.dequeue_buffer() else {
+                return;
+            };
+            let channels = state.format.channels() as usize;
+            if state.negotiated.is_none() || channels == 0 {
+                return;
+            }
+            let Some(data) = buffer.datas_mut().first_mut() else {
+                return;
+            };
+
+            let offset = data.chunk().offset() as usize;
+            let size = data.chunk().size() as usize;
+            let Some(bytes) = data.data() else {
+                return;
+            };
+            let end = (offset + size).min(bytes.len());
+
+            state.samples.clear();
+            state.samples.extend(
+                bytes[offset.min(end)..end]
+                    .chunks_exact(4)
+                    .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]])),
+            );
+            state
+                .channel_mode
+                .apply_into(&state.samples, channels, &mut state.mixed);
+
+            // Overflow is counted by the queue (see QueueStats)
+            state.producer.push(&state.mixed);
+        })
+        .state_changed(move |_, state, _, new| match new {
+            StreamState::Error(error) => {
+                eprintln!("PipeWire capture stream failed: {}", error);
+                match state.init.borrow_mut().take() {
+                    Some(init_tx) => {
+                        let _ = init_tx.send(Err(anyhow!("PipeWire stream failed: {}", error)));
+                    }
+                    None => {
+                        state
+                            .producer
+                            .handle()
+                            .record_error(format!("PipeWire stream failed: {}", error));
+                        if let Some(callback) = &on_device_event {
+                            callback(DeviceEvent::Lost {
+                                device: state.source.borrow().clone(),
+                                error,
+                            });
+                        }
+                    }
+                }
+                mainloop.quit();
+            }
+            StreamState::Unconnected => mainloop.quit(),
+            _ => {}
+        })
+        .register()
+        .map_err(|e| anyhow!("Failed to listen on PipeWire stream: {}", e))?;
+
+    // Closing the queue is the only stop signal, so check it from the loop
+    let started = Instant::now();
+    let timer = connection.mainloop.loop_().add_timer({
+        let mainloop = connection.mainloop.clone();
+        move |_| {
+            if queue.is_closed() {
+                mainloop.quit();
+            } else if started.elapsed() > LINK_TIMEOUT {
+                // A target that never links would otherwise block stream() forever
+                if let Some(init_tx) = init.borrow_mut().take() {
+                    let _ = init_tx.send(Err(anyhow!("PipeWire never linked the capture stream")));
+                    mainloop.quit();
+                }
+            }
+        }
+    });
+    timer
+        .update_timer(Some(CLOSE_POLL_INTERVAL), Some(CLOSE_POLL_INTERVAL))
+        .into_sync_result()
+        .map_err(|e| anyhow!("Failed to start PipeWire timer: {}", e))?;
+
+    // Plain averaging is left to PipeWire; other modes need every channel
+    let mut requested = AudioInfoRaw::new();
+    requested.set_format(AudioFormat::F32LE);
+    if !channel_mode.needs_native_channels() {
+        requested.set_channels(1);
+    }
+    let format = format_param(requested)?;
+    let mut params = [Pod::from_bytes(&format).ok_or_else(|| anyhow!("Invalid PipeWire format"))?];
+
+    stream
+        .connect(
+            Direction::Input,
+            None,
+            StreamFlags::AUTOCONNECT | StreamFlags::MAP_BUFFERS,
+            &mut params,
+        )
+        .map_err(|e| anyhow!("Failed to connect PipeWire stream: {}", e))?;
+
+    connection.mainloop.run();
+
+    let _ = stream.disconnect();
+    Ok(())
+}
+
+// Stream properties that pick what is captured. `<sink>.monitor` records the sink node's
+// monitor ports, as the pulse server does. A device the user chose is never swapped for
+// another one behind their back; only the default follows the session manager.
+fn target_properties(target: Option<&str>) -> Vec<(&'static str, &str)> {
+    match target.map(|id| id.strip_suffix(MONITOR_SUFFIX).ok_or(id)) {
+        Some(Ok(sink)) => vec![
+            (*pw::keys::STREAM_CAPTURE_SINK, "true"),
+            (*pw::keys::TARGET_OBJECT, sink),
+            (DONT_RECONNECT, "true"),
+        ],
+        Some(Err(source)) => vec![(*pw::keys::TARGET_OBJECT, source), (DONT_RECONNECT, "true")],
+        None => vec![(*pw::keys::STREAM_CAPTURE_SINK, "true")],
+    }
+}
+
+// Node name from a `default.audio.sink` metadata value (`{"name":"<node>"}`)
+fn default_sink_name(value: &str) -> Option<String> {
+    serde_json::from_str::<serde_json::Value>(value)
+        .ok()?
+        .get("name")?
+        .as_str()
+        .map(str::to_string)
+}
+
+// Follows `default.audio.sink` in the session manager's metadata and raises
+// DeviceEvent::Changed when the default sink switches during capture
+struct DefaultSinkWatcher {
+    _registry_listener: pw::registry::Listener,
+    _metadata: Rc<RefCell<Option<(pw::metadata::Metadata, pw::metadata::MetadataListener)>>>,
+    _registry: Rc<pw::registry::Registry>,
+}
+
+impl DefaultSinkWatcher {
+    fn new(
+        connection: &Connection,
+        source: Rc<RefCell<String>>,
+        queue: QueueHandle,
+        on_device_event: Option<DeviceEventCallback>,
+    ) -> Result<Self> {
+        let registry = Rc::new(
+            connection
+                .core
+                .get_registry()
+                .map_err(|e| anyhow!("Failed to get PipeWire registry: {}", e))?,
+        );
+        let metadata = Rc::new(RefCell::new(None));
+
+        let registry_listener = registry
+            .add_listener_local()
+            .global({
+                let registry = Rc::downgrade(&registry);
+                let metadata = metadata.clone();
+                move |global| {
+                    if global.type_ != ObjectType::Metadata
+                        || global.props.and_then(|p| p.get("metadata.name"))
+                            != Some(DEFAULT_METADATA)
+                    {
+                        return;
+                    }
+                    let Some(registry) = registry.upgrade() else {
+                        return;
+                    };
+                    let bound: pw::metadata::Metadata = match registry.bind(global) {
+                        Ok(bound) => bound,
+                        Err(e) => {
+                            eprintln!("Failed to bind PipeWire default metadata: {}", e);
+                            return;
+                        }
+                    };
+
+                    // The first value is the sink we linked to; later ones are switches
+                    let known = Rc::new(Cell::new(false));
+                    let source = source.clone();
+                    let queue = queue.clone();
+                    let on_device_event = on_device_event.clone();
+                    let listener = bound
+                        .add_listener_local()
+                        .property(move |_, key, _, value| {
+                            if key != Some(DEFAULT_SINK_KEY) {
+                                return 0;
+                            }
+                            let Some(monitor) = value
+                                .and_then(default_sink_name)
+                                .map(|sink| format!("{}{}", sink, MONITOR_SUFFIX))
+                            else {
+                                return 0;
+                            };
+                            if *source.borrow() == monitor {
+                                return 0;
+                            }
+
+                            *source.borrow_mut() = monitor.clone();
+                            queue.set_source(&monitor);
+                            if known.replace(true) {
+                                if let Some(callback) = &on_device_event {
+                                    callback(DeviceEvent::Changed { device: monitor });
+                                }
+                            }
+                            0
+                        })
+                        .register();
+                    *metadata.borrow_mut() = Some((bound, listener));
+                }
+            })
+            .register();
+
+        Ok(Self {
+            _registry_listener: registry_listener,
+            _metadata: metadata,
+            _registry: registry,
+        })
+    }
+}
+
+// EnumFormat pod for F32 audio; unset rate and channels accept the graph's native ones
+fn format_param(info: AudioInfoRaw) -> Result<Vec<u8>> {
+    let object = Object {
+        type_: SpaTypes::ObjectParamFormat.as_raw(),
+        id: ParamType::EnumFormat.as_raw(),
+        properties: info.into(),
+    };
+    PodSerializer::serialize(Cursor::new(Vec::new()), &Value::Object(object))
+        .map(|(cursor, _)| cursor.into_inner())
+        .map_err(|e| anyhow!("Failed to build PipeWire format: {:?}", e))
+}
+
+// Main loop, context and core; fields drop in that reverse order
+struct Connection {
+    core: pw::core::Core,
+    _context: pw::context::Context,
+    mainloop: pw::main_loop::MainLoop,
+}
+
+impl Connection {
+    fn open() -> Result<Self> {
+        let mainloop = pw::main_loop::MainLoop::new(None)
+            .map_err(|e| anyhow!("Failed to create PipeWire main loop: {}", e))?;
+        let context = pw::context::Context::new(&mainloop)
+            .map_err(|e| anyhow!("Failed to create PipeWire context: {}", e))?;
+        let core = context
+            .connect(None)
+            .map_err(|e| anyhow!("Failed to connect to PipeWire: {}", e))?;
+
+        Ok(Self {
+            core,
+            _context: context,
+            mainloop,
+        })
+    }
+
+    // Runs the loop until the server has answered everything sent so far
+    fn roundtrip(&self) -> Result<()> {
+        let done = Rc::new(Cell::new(false));
+        let pending = self
+            .core
+            .sync(0)
+            .map_err(|e| anyhow!("Failed to sync with PipeWire: {}", e))?;
+
+        let _listener = self
+            .core
+            .add_listener_local()
+            .done({
+                let done = done.clone();
+                let mainloop = self.mainloop.clone();
+                move |id, seq| {
+                    if id == pw::core::PW_ID_CORE && seq == pending {
+                        done.set(true);
+                        mainloop.quit();
+                    }
+                }
+            })
+            .error({
+                let mainloop = self.mainloop.clone();
+                move |id, _, _, message| {
+                    eprintln!("PipeWire error on object {}: {}", id, message);
+                    if id == pw::core::PW_ID_CORE {
+                        mainloop.quit();
+                    }
+                }
+            })
+            .register();
+        self.mainloop.run();
+
+        if !done.get() {
+            return Err(anyhow!("PipeWire connection failed during roundtrip"));
+        }
+        Ok(())
+    }
+}
+
+impl Drop for SpeakerStream {
+    fn drop(&mut self) {
+        self.queue.handle().close();
+        if let Some(thread) = self.capture_thread.take() {
+            let _ = thread.join();
+        }
+
+        let stats = self.queue.handle().stats();
+        if stats.dropped > 0 {
+            eprintln!(
+                "System audio capture dropped {} of {} samples ({} overflows)",
+                stats.dropped, stats.received, stats.overflows
+            );
+        }
+    }
+}
+
+impl Stream for SpeakerStream {
+    type Item = f32;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.queue.poll_sample(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value<'a>(props: &[(&str, &'a str)], key: &str) -> Option<&'a str> {
+        props.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+    }
+
+    #[test]
+    fn chosen_monitor_captures_that_sink_without_reconnecting() {
+        let props = target_properties(Some("alsa_output.usb-headset.monitor"));
+        assert_eq!(value(&props, *pw::keys::STREAM_CAPTURE_SINK), Some("true"));
+        assert_eq!(
+            value(&props, *pw::keys::TARGET_OBJECT),
+            Some("alsa_output.usb-headset")
+        );
+        assert_eq!(value(&props, DONT_RECONNECT), Some("true"));
+    }
+
+    #[test]
+    fn chosen_source_is_targeted_directly() {
+        let props = target_properties(Some("alsa_input.usb-mic"));
+        assert_eq!(value(&props, *pw::keys::STREAM_CAPTURE_SINK), None);
+        assert_eq!(
+            value(&props, *pw::keys::TARGET_OBJECT),
+            Some("alsa_input.usb-mic")
+        );
+        assert_eq!(value(&props, DONT_RECONNECT), Some("true"));
+    }
+
+    #[test]
+    fn default_follows_the_session_manager() {
+        let props = target_properties(None);
+        assert_eq!(value(&props, *pw::keys::STREAM_CAPTURE_SINK), Some("true"));
+        assert_eq!(value(&props, *pw::keys::TARGET_OBJECT), None);
+        assert_eq!(value(&props, DONT_RECONNECT), None);
+    }
+
+    #[test]
+    fn default_sink_metadata_is_parsed() {
+        assert_eq!(
+            default_sink_name(r#"{ "name": "alsa_output.pci-0000_00_1f.3.analog-stereo" }"#),
+            Some("alsa_output.pci-0000_00_1f.3.analog-stereo".to_string())
+        );
+        assert_eq!(default_sink_name("alsa_output.raw"), None);
+        assert_eq!(default_sink_name(r#"{ "id": 42 }"#), None);
+    }
+}
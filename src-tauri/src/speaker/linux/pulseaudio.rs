@@ -0,0 +1,885 @@
+// Meetwings linux speaker input and stream over PulseAudio (or PipeWire's pulse server)
+use anyhow::{anyhow, Result};
+use futures_util::Stream;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::task::Poll;
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+use libpulse_binding as pulse;
+use libpulse_simple_binding as psimple;
+
+use psimple::Simple;
+use pulse::callbacks::ListResult;
+use pulse::context::{Context, FlagSet as ContextFlagSet, State as ContextState};
+use pulse::def::INVALID_INDEX;
+use pulse::mainloop::standard::{IterateResult, Mainloop};
+use pulse::operation::{Operation, State as OperationState};
+use pulse::proplist::properties;
+use pulse::sample::{Format, Spec};
+use pulse::stream::Direction;
+
+use crate::speaker::downmix::ChannelMode;
+use crate::speaker::frames::AudioFrame;
+use crate::speaker::queue::{
+    sample_queue, QueueHandle, SampleConsumer, SampleProducer, QUEUE_CAPACITY,
+};
+use crate::speaker::{AudioDevice, AudioDeviceKind, DeviceEvent, DeviceEventCallback};
+
+const DEFAULT_SAMPLE_RATE: u32 = 44_100;
+const DEFAULT_MONITOR: &str = "@DEFAULT_MONITOR@";
+const DEFAULT_SINK_POLL_INTERVAL: Duration = Duration::from_secs(1);
+const REOPEN_ATTEMPTS: u32 = 10;
+const REOPEN_BACKOFF: Duration = Duration::from_millis(500);
+pub(super) const APP_DEVICE_PREFIX: &str = "app:"; // `app:<binary>` captures one application
+const APP_SINK_PREFIX: &str = "meetwings_app_";
+const APP_ROUTE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+const APP_LOOPBACK_LATENCY_MS: u32 = 30;
+
+pub struct SpeakerInput {
+    source_name: Option<String>,
+    on_device_event: Option<DeviceEventCallback>,
+    channel_mode: ChannelMode,
+}
+
+impl SpeakerInput {
+    pub fn new(device_id: Option<String>) -> Result<Self> {
+        // For Linux, device_id is the PulseAudio source name or `app:<binary>`
+        Ok(Self {
+            source_name: device_id,
+            on_device_event: None,
+            channel_mode: ChannelMode::default(),
+        })
+    }
+
+    pub fn set_device_event_callback(&mut self, callback: DeviceEventCallback) {
+        self.on_device_event = Some(callback);
+    }
+
+    pub fn set_channel_mode(&mut self, mode: ChannelMode) {
+        self.channel_mode = mode;
+    }
+
+    // Lists PulseAudio monitor sources (one per sink), then applications playing audio
+    pub fn list_devices() -> Result<Vec<AudioDevice>> {
+        let mut introspector = PulseIntrospector::connect()?;
+        let default_monitor = introspector.default_sink_name()?.map(|n| monitor_of(&n));
+
+        let devices = Rc::new(RefCell::new(Vec::new()));
+        let devices_clone = devices.clone();
+        let op = introspector
+            .context
+            .introspect()
+            .get_source_info_list(move |result| {
+                if let ListResult::Item(info) = result {
+                    if info.monitor_of_sink.is_none() {
+                        return;
+                    }
+                    let Some(id) = info.name.as_ref().map(|n| n.to_string()) else {
+                        return;
+                    };
+                    let name = info
+                        .description
+                        .as_ref()
+                        .map(|d| d.to_string())
+                        .unwrap_or_else(|| id.clone());
+
+                    devices_clone.borrow_mut().push(AudioDevice {
+                        is_default: default_monitor.as_deref() == Some(id.as_str()),
+                        id,
+                        name,
+                        kind: AudioDeviceKind::Monitor,
+                        channels: info.sample_spec.channels as u16,
+                        sample_rate: info.sample_spec.rate,
+                    });
+                }
+            });
+        introspector.wait_for(op)?;
+
+        let mut devices = devices.borrow().clone();
+        for input in introspector.sink_inputs()? {
+            let id = format!("{}{}", APP_DEVICE_PREFIX, input.app_id);
+            if devices.iter().any(|d| d.id == id) {
+                continue; // One entry per application, however many streams it has
+            }
+            devices.push(AudioDevice {
+                id,
+                name: input.app_name,
+                kind: AudioDeviceKind::Application,
+                is_default: false,
+                channels: input.channels as u16,
+                sample_rate: input.rate,
+            });
+        }
+        Ok(devices)
+    }
+
+    pub fn stream(self) -> SpeakerStream {
+        let (producer, consumer) = sample_queue(QUEUE_CAPACITY);
+        let (init_tx, init_rx) = std::sync::mpsc::channel();
+
+        let source_name = self.source_name;
+        let on_device_event = self.on_device_event;
+        let channel_mode = self.channel_mode;
+
+        let mut capture_thread = Some(thread::spawn(move || {
+            if let Err(e) = SpeakerStream::capture_audio_loop(
+                producer,
+                source_name.as_deref(),
+                init_tx,
+                on_device_event,
+                channel_mode,
+            ) {
+                eprintln!("Audio capture loop failed: {}", e);
+            }
+        }));
+
+        let (sample_rate, channels, init_success) = match init_rx.recv() {
+            Ok(Ok((sr, channels))) => (sr, channels, true),
+            Ok(Err(e)) => {
+                eprintln!("Audio initialization failed: {}", e);
+                consumer.handle().record_error(&e);
+                (DEFAULT_SAMPLE_RATE, 1, false)
+            }
+            Err(e) => {
+                eprintln!("Failed to receive audio init signal: {}", e);
+                (DEFAULT_SAMPLE_RATE, 1, false)
+            }
+        };
+
+        if !init_success {
+            consumer.handle().close();
+
+            if let Some(handle) = capture_thread.take() {
+                let _ = handle.join();
+            }
+        }
+
+        SpeakerStream {
+            queue: consumer,
+            capture_thread,
+            sample_rate,
+            channels,
+        }
+    }
+}
+
+pub struct SpeakerStream {
+    queue: SampleConsumer,
+    capture_thread: Option<thread::JoinHandle<()>>,
+    sample_rate: u32,
+    channels: usize,
+}
+
+impl SpeakerStream {
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn backend(&self) -> &'static str {
+        "pulseaudio"
+    }
+
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    pub fn queue(&self) -> &QueueHandle {
+        self.queue.handle()
+    }
+
+    pub fn poll_frame(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+        frame_size: usize,
+    ) -> Poll<Option<AudioFrame>> {
+        self.queue.poll_frame(cx, frame_size, self.sample_rate)
+    }
+
+    fn capture_audio_loop(
+        mut producer: SampleProducer,
+        source_name: Option<&str>,
+        init_tx: std::sync::mpsc::Sender<Result<(u32, usize)>>,
+        on_device_event: Option<DeviceEventCallback>,
+        channel_mode: ChannelMode,
+    ) -> Result<()> {
+        let notify = |event: DeviceEvent| {
+            if let Some(callback) = &on_device_event {
+                callback(event);
+            }
+        };
+
+        // A single application is captured from the monitor of a private sink
+        let mut app_route = match source_name.and_then(|s| s.strip_prefix(APP_DEVICE_PREFIX)) {
+            Some(app_id) => match AppRoute::create(app_id) {
+                Ok(route) => Some(route),
+                Err(e) => {
+                    let _ = init_tx.send(Err(e));
+                    return Ok(());
+                }
+            },
+            None => None,
+        };
+        let route_monitor = app_route.as_ref().map(AppRoute::monitor);
+        let source_name = route_monitor.as_deref().or(source_name);
+
+        // Without an explicit source we follow whichever sink is the default
+        let follow_default = source_name.is_none();
+        let mut current_source = source_name
+            .map(|s| s.to_string())
+            .or_else(get_default_monitor_source)
+            .unwrap_or_else(|| DEFAULT_MONITOR.to_string());
+
+        // Capture at the source's native rate so PulseAudio doesn't resample for us.
+        // Later reopens keep this spec; the server converts if the new source differs.
+        let (native_rate, native_channels) = PulseIntrospector::connect()
+            .and_then(|mut introspector| introspector.source_spec(&current_source))
+            .unwrap_or_else(|e| {
+                eprintln!("Failed to query source sample spec: {}", e);
+                None
+            })
+            .unwrap_or((DEFAULT_SAMPLE_RATE, 1));
+
+        // Plain averaging is left to the server; other modes need every channel
+        let capture_channels = if channel_mode.needs_native_channels() {
+            native_channels.max(1)
+        } else {
+            1
+        };
+
+        let spec = Spec {
+            format: Format::F32le,
+            channels: capture_channels,
+            rate: native_rate,
+        };
+        let channels = capture_channels as usize;
+
+        if !spec.is_valid() {
+            return Err(anyhow!("Invalid audio specification"));
+        }
+
+        let mut simple = match open_record_stream(Some(&current_source), &spec) {
+            Ok(simple) => {
+                let _ = init_tx.send(Ok((spec.rate, channel_mode.output_channels(channels))));
+                simple
+            }
+            Err(e) => {
+                let _ = init_tx.send(Err(e));
+                return Ok(());
+            }
+        };
+
+        // Second connection used only to watch for default sink changes
+        let mut watcher = follow_default.then(DefaultSinkWatcher::new).flatten();
+        if let Some(monitor) = watcher.as_ref().and_then(|w| w.default_monitor()) {
+            current_source = monitor;
+        }
+        producer.handle().set_source(&current_source);
+
+        // Buffer for reading audio data: 1024 frames of f32 samples
+        let mut buffer = vec![0u8; 1024 * channels * 4];
+        let mut samples = Vec::with_capacity(1024 * channels);
+        let mut mixed = Vec::with_capacity(1024 * channels);
+
+        loop {
+            if producer.handle().is_closed() {
+                break;
+            }
+
+            // Pick up streams the application opened since the last poll
+            if let Some(route) = app_route.as_mut() {
+                route.poll(producer.handle());
+            }
+
+            // Default sink switched (e.g. headset plugged in): reopen on its monitor
+            if let Some(new_monitor) = watcher.as_mut().and_then(|w| w.poll_changed()) {
+                match open_record_stream(Some(DEFAULT_MONITOR), &spec) {
+                    Ok(reopened) => {
+                        simple = reopened;
+                        current_source = new_monitor.clone();
+                        producer.handle().set_source(&current_source);
+                        notify(DeviceEvent::Changed {
+                            device: new_monitor,
+                        });
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to follow default sink change: {}", e);
+                        producer.handle().record_error(&e);
+                    }
+                }
+            }
+
+            match simple.read(&mut buffer) {
+                Ok(_) => {
+                    // Convert byte buffer to f32 samples
+                    samples.clear();
+                    samples.extend(
+                        buffer.chunks_exact(4).map(|chunk| {
+                            f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]])
+                        }),
+                    );
+                    channel_mode.apply_into(&samples, channels, &mut mixed);
+
+                    // Overflow is counted by the queue (see QueueStats)
+                    producer.push(&mixed);
+                }
+                Err(e) => {
+                    eprintln!("PulseAudio read error: {}", e);
+                    producer
+                        .handle()
+                        .record_error(format!("PulseAudio read error: {}", e));
+                    notify(DeviceEvent::Lost {
+                        device: current_source.clone(),
+                        error: e.to_string(),
+                    });
+
+                    // Falling back to the default monitor would capture every application
+                    if app_route.is_some() {
+                        break;
+                    }
+
+                    // Retry the chosen source, or whatever is now the default; a chosen
+                    // source is never swapped for another device behind the user's back
+                    let retry_source = if follow_default {
+                        DEFAULT_MONITOR
+                    } else {
+                        current_source.as_str()
+                    };
+                    match reopen_monitor(retry_source, &spec, producer.handle()) {
+                        Some(reopened) => {
+                            simple = reopened;
+                            if follow_default {
+                                current_source = watcher
+                                    .as_mut()
+                                    .and_then(|w| w.refresh())
+                                    .unwrap_or_else(|| DEFAULT_MONITOR.to_string());
+                            }
+                            producer.handle().set_source(&current_source);
+                            notify(DeviceEvent::Changed {
+                                device: current_source.clone(),
+                            });
+                        }
+                        None => {
+                            eprintln!("Giving up on system audio capture after device loss");
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        // End the stream so consumers see the capture finish
+        producer.handle().close();
+
+        Ok(())
+    }
+}
+
+fn open_record_stream(source_name: Option<&str>, spec: &Spec) -> Result<Simple> {
+    Simple::new(
+        None,                   // Use default server
+        "Meetwings",            // Application name
+        Direction::Record,      // Record direction
+        source_name,            // Source name (monitor)
+        "System Audio Capture", // Stream description
+        spec,                   // Sample specification
+        None,                   // Channel map (use default)
+        None,                   // Buffer attributes (use default)
+    )
+    .map_err(|e| anyhow!("Failed to create PulseAudio simple connection: {}", e))
+}
+
+// Retries a source with backoff instead of spinning on read errors
+fn reopen_monitor(source_name: &str, spec: &Spec, queue: &QueueHandle) -> Option<Simple> {
+    for attempt in 1..=REOPEN_ATTEMPTS {
+        if queue.is_closed() {
+            return None;
+        }
+
+        thread::sleep(REOPEN_BACKOFF);
+
+        match open_record_stream(Some(source_name), spec) {
+            Ok(simple) => return Some(simple),
+            Err(e) => {
+                eprintln!(
+                    "Reopen attempt {}/{} failed: {}",
+                    attempt, REOPEN_ATTEMPTS, e
+                );
+                queue.record_error(&e);
+            }
+        }
+    }
+
+    None
+}
+
+// Run at startup so application streams stranded on a crashed run's capture sink are
+// returned to the speakers without waiting for the next per-application capture
+pub(super) fn unload_stale_routes() -> Result<()> {
+    PulseIntrospector::connect()?.unload_stale_routes();
+    Ok(())
+}
+
+fn monitor_of(sink_name: &str) -> String {
+    format!("{}.monitor", sink_name)
+}
+
+// Routes one application's playback through a private null sink so that sink's monitor
+// carries only that application. A loopback plays the sink on the speakers the
+// application was using, so the user keeps hearing it. Dropping the route undoes this.
+struct AppRoute {
+    introspector: PulseIntrospector,
+    app_id: String,
+    sink_name: String,
+    sink_module: u32,
+    loopback_module: Option<u32>, // Loaded once we know where the application was playing
+    moved: HashMap<u32, u32>,     // Sink input -> sink it was moved from
+    last_poll: Instant,
+}
+
+impl AppRoute {
+    fn create(app_id: &str) -> Result<Self> {
+        let mut introspector = PulseIntrospector::connect()?;
+        introspector.unload_stale_routes();
+
+        let sink_name = format!("{}{}", APP_SINK_PREFIX, std::process::id());
+        let sink_module = introspector.load_module(
+            "module-null-sink",
+            &format!(
+                "sink_name={} sink_properties=device.description=Meetwings-capture",
+                sink_name
+            ),
+        )?;
+
+        // The application may not be playing yet; its streams are moved once they appear
+        let mut route = Self {
+            introspector,
+            app_id: app_id.to_string(),
+            sink_name,
+            sink_module,
+            loopback_module: None,
+            moved: HashMap::new(),
+            last_poll: Instant::now(),
+        };
+        route.sync()?;
+        Ok(route)
+    }
+
+    fn monitor(&self) -> String {
+        monitor_of(&self.sink_name)
+    }
+
+    // Routing failures are recorded on the queue so they show up in the diagnostics
+    fn poll(&mut self, queue: &QueueHandle) {
+        if self.last_poll.elapsed() < APP_ROUTE_POLL_INTERVAL {
+            return;
+        }
+        self.last_poll = Instant::now();
+
+        if let Err(e) = self.sync() {
+            eprintln!("Failed to route {} audio: {}", self.app_id, e);
+            queue.record_error(format!("Failed to route {} audio: {}", self.app_id, e));
+        }
+    }
+
+    // Moves the application's streams that aren't on our sink yet
+    fn sync(&mut self) -> Result<()> {
+        let sink_index = self
+            .introspector
+            .sink_index(&self.sink_name)?
+            .ok_or_else(|| anyhow!("Capture sink {} disappeared", self.sink_name))?;
+        let inputs = self.introspector.sink_inputs()?;
+        self.moved
+            .retain(|index, _| inputs.iter().any(|input| input.index == *index));
+
+        for input in inputs
+            .iter()
+            .filter(|input| input.app_id == self.app_id && input.sink != sink_index)
+        {
+            // Streams from several sinks all play back on the first one seen
+            if self.loopback_module.is_none() {
+                let speakers = self
+                    .introspector
+                    .sink_name(input.sink)?
+                    .ok_or_else(|| anyhow!("Sink {} not found", input.sink))?;
+                self.loopback_module = Some(self.introspector.load_module(
+                    "module-loopback",
+                    &format!(
+                        "source={} sink={} latency_msec={} source_dont_move=true sink_dont_move=true",
+                        self.monitor(),
+                        speakers,
+                        APP_LOOPBACK_LATENCY_MS
+                    ),
+                )?);
+            }
+
+            if self
+                .introspector
+                .move_sink_input(input.index, &self.sink_name)?
+            {
+                self.moved.insert(input.index, input.sink);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for AppRoute {
+    fn drop(&mut self) {
+        // Put the streams back before their sink goes away, or they'd land on the default
+        for (&input, &sink) in &self.moved {
+            if let Err(e) = self.introspector.restore_sink_input(input, sink) {
+                eprintln!("Failed to restore {} audio routing: {}", self.app_id, e);
+            }
+        }
+        if let Some(module) = self.loopback_module {
+            let _ = self.introspector.unload_module(module);
+        }
+        let _ = self.introspector.unload_module(self.sink_module);
+    }
+}
+
+// An application's playback stream
+struct SinkInput {
+    index: u32,
+    sink: u32,
+    app_id: String, // Process binary, falling back to the application name
+    app_name: String,
+    channels: u8,
+    rate: u32,
+}
+
+// Polls the server's default sink at a fixed interval
+struct DefaultSinkWatcher {
+    introspector: PulseIntrospector,
+    default_sink: Option<String>,
+    last_poll: Instant,
+}
+
+impl DefaultSinkWatcher {
+    fn new() -> Option<Self> {
+        let mut introspector = match PulseIntrospector::connect() {
+            Ok(introspector) => introspector,
+            Err(e) => {
+                eprintln!("Default sink tracking unavailable: {}", e);
+                return None;
+            }
+        };
+        let default_sink = introspector.default_sink_name().ok().flatten();
+
+        Some(Self {
+            introspector,
+            default_sink,
+            last_poll: Instant::now(),
+        })
+    }
+
+    // Returns the new default monitor name when the default sink has changed
+    fn poll_changed(&mut self) -> Option<String> {
+        if self.last_poll.elapsed() < DEFAULT_SINK_POLL_INTERVAL {
+            return None;
+        }
+        self.last_poll = Instant::now();
+
+        match self.introspector.default_sink_name() {
+            Ok(Some(sink)) if self.default_sink.as_deref() != Some(sink.as_str()) => {
+                let monitor = monitor_of(&sink);
+                self.default_sink = Some(sink);
+                Some(monitor)
+            }
+            Ok(_) => None,
+            Err(e) => {
+                eprintln!("Failed to query default sink: {}", e);
+                None
+            }
+        }
+    }
+
+    fn default_monitor(&self) -> Option<String> {
+        self.default_sink.as_deref().map(monitor_of)
+    }
+
+    // Re-reads the default sink without reporting a change
+    fn refresh(&mut self) -> Option<String> {
+        self.default_sink = self.introspector.default_sink_name().ok().flatten();
+        self.last_poll = Instant::now();
+        self.default_monitor()
+    }
+}
+
+fn get_default_monitor_source() -> Option<String> {
+    Some(DEFAULT_MONITOR.to_string())
+}
+
+// Blocking PulseAudio context for one-off introspection queries
+struct PulseIntrospector {
+    mainloop: Mainloop,
+    context: Context,
+}
+
+impl PulseIntrospector {
+    fn connect() -> Result<Self> {
+        let mut mainloop =
+            Mainloop::new().ok_or_else(|| anyhow!("Failed to create PulseAudio mainloop"))?;
+        let mut context = Context::new(&mainloop, "Meetwings")
+            .ok_or_else(|| anyhow!("Failed to create PulseAudio context"))?;
+
+        context
+            .connect(None, ContextFlagSet::NOFLAGS, None)
+            .map_err(|e| anyhow!("Failed to connect to PulseAudio: {}", e))?;
+
+        loop {
+            match mainloop.iterate(true) {
+                IterateResult::Success(_) => {}
+                IterateResult::Quit(_) | IterateResult::Err(_) => {
+                    return Err(anyhow!("PulseAudio mainloop failed while connecting"));
+                }
+            }
+
+            match context.get_state() {
+                ContextState::Ready => break,
+                ContextState::Failed | ContextState::Terminated => {
+                    return Err(anyhow!("PulseAudio context failed to connect"));
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Self { mainloop, context })
+    }
+
+    fn default_sink_name(&mut self) -> Result<Option<String>> {
+        let default_sink = Rc::new(RefCell::new(None::<String>));
+        let default_sink_clone = default_sink.clone();
+        let op = self.context.introspect().get_server_info(move |info| {
+            *default_sink_clone.borrow_mut() =
+                info.default_sink_name.as_ref().map(|n| n.to_string());
+        });
+        self.wait_for(op)?;
+
+        let name = default_sink.borrow().clone();
+        Ok(name)
+    }
+
+    // Native rate and channel count of a source; accepts names such as @DEFAULT_MONITOR@
+    fn source_spec(&mut self, source_name: &str) -> Result<Option<(u32, u8)>> {
+        let spec = Rc::new(RefCell::new(None::<(u32, u8)>));
+        let spec_clone = spec.clone();
+        let op = self
+            .context
+            .introspect()
+            .get_source_info_by_name(source_name, move |result| {
+                if let ListResult::Item(info) = result {
+                    *spec_clone.borrow_mut() =
+                        Some((info.sample_spec.rate, info.sample_spec.channels));
+                }
+            });
+        self.wait_for(op)?;
+
+        let spec = *spec.borrow();
+        Ok(spec)
+    }
+
+    // Playback streams opened by applications; module-owned streams (loopbacks) are skipped
+    fn sink_inputs(&mut self) -> Result<Vec<SinkInput>> {
+        let inputs = Rc::new(RefCell::new(Vec::new()));
+        let inputs_clone = inputs.clone();
+        let op = self
+            .context
+            .introspect()
+            .get_sink_input_info_list(move |result| {
+                if let ListResult::Item(info) = result {
+                    if info.owner_module.is_some() {
+                        return;
+                    }
+                    let name = info.proplist.get_str(properties::APPLICATION_NAME);
+                    let binary = info
+                        .proplist
+                        .get_str(properties::APPLICATION_PROCESS_BINARY);
+                    let Some(app_id) = binary.or_else(|| name.clone()) else {
+                        return;
+                    };
+
+                    inputs_clone.borrow_mut().push(SinkInput {
+                        index: info.index,
+                        sink: info.sink,
+                        app_name: name.unwrap_or_else(|| app_id.clone()),
+                        app_id,
+                        channels: info.sample_spec.channels,
+                        rate: info.sample_spec.rate,
+                    });
+                }
+            });
+        self.wait_for(op)?;
+
+        let inputs = inputs.take();
+        Ok(inputs)
+    }
+
+    fn sink_index(&mut self, sink_name: &str) -> Result<Option<u32>> {
+        let index = Rc::new(Cell::new(None));
+        let index_clone = index.clone();
+        let op = self
+            .context
+            .introspect()
+            .get_sink_info_by_name(sink_name, move |result| {
+                if let ListResult::Item(info) = result {
+                    index_clone.set(Some(info.index));
+                }
+            });
+        self.wait_for(op)?;
+        Ok(index.get())
+    }
+
+    fn sink_name(&mut self, sink_index: u32) -> Result<Option<String>> {
+        let name = Rc::new(RefCell::new(None::<String>));
+        let name_clone = name.clone();
+        let op = self
+            .context
+            .introspect()
+            .get_sink_info_by_index(sink_index, move |result| {
+                if let ListResult::Item(info) = result {
+                    *name_clone.borrow_mut() = info.name.as_ref().map(|n| n.to_string());
+                }
+            });
+        self.wait_for(op)?;
+
+        let name = name.borrow().clone();
+        Ok(name)
+    }
+
+    fn load_module(&mut self, name: &str, argument: &str) -> Result<u32> {
+        let index = Rc::new(Cell::new(INVALID_INDEX));
+        let index_clone = index.clone();
+        let op = self
+            .context
+            .introspect()
+            .load_module(name, argument, move |i| index_clone.set(i));
+        self.wait_for(op)?;
+
+        match index.get() {
+            INVALID_INDEX => Err(anyhow!("Failed to load {} {}", name, argument)),
+            index => Ok(index),
+        }
+    }
+
+    fn unload_module(&mut self, index: u32) -> Result<()> {
+        let op = self.context.introspect().unload_module(index, |_| {});
+        self.wait_for(op)
+    }
+
+    // False when the stream went away or refused to move
+    fn move_sink_input(&mut self, index: u32, sink_name: &str) -> Result<bool> {
+        let moved = Rc::new(Cell::new(false));
+        let moved_clone = moved.clone();
+        let op = self.context.introspect().move_sink_input_by_name(
+            index,
+            sink_name,
+            Some(Box::new(move |success| moved_clone.set(success))),
+        );
+        self.wait_for(op)?;
+        Ok(moved.get())
+    }
+
+    fn restore_sink_input(&mut self, index: u32, sink_index: u32) -> Result<()> {
+        let op = self
+            .context
+            .introspect()
+            .move_sink_input_by_index(index, sink_index, None);
+        self.wait_for(op)
+    }
+
+    // Unloads capture sinks and loopbacks left behind by a previous run that crashed
+    fn unload_stale_routes(&mut self) {
+        let own_pid = std::process::id().to_string();
+        let stale = Rc::new(RefCell::new(Vec::new()));
+        let stale_clone = stale.clone();
+        let op = self
+            .context
+            .introspect()
+            .get_module_info_list(move |result| {
+                if let ListResult::Item(info) = result {
+                    // Our sinks are named after the owning pid; another instance may still be
+                    // running, so only routes whose process has exited are stale
+                    let Some((_, rest)) = info
+                        .argument
+                        .as_deref()
+                        .and_then(|a| a.split_once(APP_SINK_PREFIX))
+                    else {
+                        return;
+                    };
+                    let pid: String = rest.chars().take_while(char::is_ascii_digit).collect();
+                    if !pid.is_empty()
+                        && pid != own_pid
+                        && !std::path::Path::new("/proc").join(&pid).exists()
+                    {
+                        stale_clone.borrow_mut().push(info.index);
+                    }
+                }
+            });
+        if let Err(e) = self.wait_for(op) {
+            warn!("Failed to list PulseAudio modules: {}", e);
+            return;
+        }
+
+        for module in stale.take() {
+            let _ = self.unload_module(module);
+        }
+    }
+
+    // Drives the mainloop until the operation completes
+    fn wait_for<T: ?Sized>(&mut self, op: Operation<T>) -> Result<()> {
+        while op.get_state() == OperationState::Running {
+            match self.mainloop.iterate(true) {
+                IterateResult::Success(_) => {}
+                IterateResult::Quit(_) | IterateResult::Err(_) => {
+                    return Err(anyhow!("PulseAudio mainloop failed during introspection"));
+                }
+            }
+        }
+
+        if op.get_state() == OperationState::Cancelled {
+            return Err(anyhow!("PulseAudio introspection was cancelled"));
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for PulseIntrospector {
+    fn drop(&mut self) {
+        self.context.disconnect();
+    }
+}
+
+impl Drop for SpeakerStream {
+    fn drop(&mut self) {
+        self.queue.handle().close();
+        if let Some(thread) = self.capture_thread.take() {
+            let _ = thread.join();
+        }
+
+        let stats = self.queue.handle().stats();
+        if stats.dropped > 0 {
+            eprintln!(
+                "System audio capture dropped {} of {} samples ({} overflows)",
+                stats.dropped, stats.received, stats.overflows
+            );
+        }
+    }
+}
+
+impl Stream for SpeakerStream {
+    type Item = f32;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.queue.poll_sample(cx)
+    }
+}
@@ -1,52 +1,128 @@
 // Meetwings macos speaker input and stream
 use anyhow::Result;
 use futures_util::Stream;
-use ringbuf::{
-    traits::{Consumer, Producer, Split},
-    HeapCons, HeapProd, HeapRb,
-};
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
-use std::sync::{Arc, Mutex};
-use std::task::{Poll, Waker};
+use std::sync::Arc;
+use std::task::Poll;
+use std::thread;
+use std::time::Duration;
 
 use ca::aggregate_device_keys as agg_keys;
 use cidre::{arc, av, cat, cf, core_audio as ca, ns, os};
+
+use super::downmix::ChannelMode;
+use super::frames::AudioFrame;
+use super::queue::{sample_queue, QueueHandle, SampleConsumer, SampleProducer, QUEUE_CAPACITY};
+use super::{AudioDevice, AudioDeviceKind, DeviceEvent, DeviceEventCallback};
+
+const DEFAULT_DEVICE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
 pub struct SpeakerInput {
     tap: ca::TapGuard, // Assuming ca::TapGuard from core-audio-rs
     agg_desc: arc::Retained<cf::DictionaryOf<cf::String, cf::Type>>,
-}
-
-struct WakerState {
-    waker: Option<Waker>,
-    has_data: bool,
+    output_uid: String,
+    on_device_event: Option<DeviceEventCallback>,
+    channel_mode: ChannelMode,
 }
 
 pub struct SpeakerStream {
-    consumer: HeapCons<f32>,
-    _device: ca::hardware::StartedDevice<ca::AggregateDevice>,
+    queue: SampleConsumer,
+    _device: Option<ca::hardware::StartedDevice<ca::AggregateDevice>>,
     _ctx: Box<Ctx>,
     _tap: ca::TapGuard,
-    waker_state: Arc<Mutex<WakerState>>,
     current_sample_rate: Arc<AtomicU32>,
+    device_changed: Arc<AtomicBool>,
+    on_device_event: Option<DeviceEventCallback>,
 }
 
 impl SpeakerStream {
     pub fn sample_rate(&self) -> u32 {
         self.current_sample_rate.load(Ordering::Acquire)
     }
+
+    pub fn backend(&self) -> &'static str {
+        "coreaudio"
+    }
+
+    pub fn channels(&self) -> usize {
+        self._ctx.channel_mode.output_channels(self._ctx.channels)
+    }
+
+    pub fn queue(&self) -> &QueueHandle {
+        self.queue.handle()
+    }
+
+    pub fn poll_frame(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+        frame_size: usize,
+    ) -> Poll<Option<AudioFrame>> {
+        self.follow_device_change();
+        let sample_rate = self.sample_rate();
+        self.queue.poll_frame(cx, frame_size, sample_rate)
+    }
+
+    fn follow_device_change(&mut self) {
+        if self.device_changed.swap(false, Ordering::AcqRel) {
+            match self.restart_on_default_device() {
+                Ok(device) => {
+                    self.queue.handle().set_source(&device);
+                    self.notify(DeviceEvent::Changed { device });
+                }
+                Err(e) => {
+                    eprintln!("Failed to follow default output device: {}", e);
+                    self.queue.handle().record_error(&e);
+                    self.notify(DeviceEvent::Lost {
+                        device: "default output".to_string(),
+                        error: e.to_string(),
+                    });
+                    self.queue.handle().close();
+                }
+            }
+        }
+    }
+
+    // Rebuilds the tap + aggregate device on the current default output, reusing the ring buffer
+    fn restart_on_default_device(&mut self) -> Result<String> {
+        // Stop the old aggregate device before its context is handed to the new one
+        self._device = None;
+
+        let input = SpeakerInput::with_tap(self._ctx.channels > 1)?;
+        let device = input.start_device(&mut self._ctx)?;
+        self._device = Some(device);
+        self._tap = input.tap;
+
+        let name = ca::System::default_output_device()?
+            .name()
+            .map(|n| n.to_string())
+            .unwrap_or(input.output_uid);
+        Ok(name)
+    }
+
+    fn notify(&self, event: DeviceEvent) {
+        if let Some(callback) = &self.on_device_event {
+            callback(event);
+        }
+    }
 }
 
 struct Ctx {
     format: arc::R<av::AudioFormat>,
-    producer: HeapProd<f32>,
-    waker_state: Arc<Mutex<WakerState>>,
+    producer: SampleProducer,
     current_sample_rate: Arc<AtomicU32>,
     consecutive_drops: Arc<AtomicU32>,
-    should_terminate: Arc<AtomicBool>,
+    channels: usize,
+    channel_mode: ChannelMode,
+    mixed: Vec<f32>, // Reused by the IO proc so it doesn't allocate per buffer
 }
 
 impl SpeakerInput {
     pub fn new(_device_id: Option<String>) -> Result<Self> {
+        Self::with_tap(false)
+    }
+
+    // Builds the global process tap (mono mixdown or stereo) on the default output
+    fn with_tap(stereo: bool) -> Result<Self> {
         let output_device = ca::System::default_output_device()?;
         let output_uid = output_device.uid()?;
 
@@ -55,7 +131,11 @@ impl SpeakerInput {
             &[output_uid.as_type_ref()],
         );
 
-        let tap_desc = ca::TapDesc::with_mono_global_tap_excluding_processes(&ns::Array::new());
+        let tap_desc = if stereo {
+            ca::TapDesc::with_stereo_global_tap_excluding_processes(&ns::Array::new())
+        } else {
+            ca::TapDesc::with_mono_global_tap_excluding_processes(&ns::Array::new())
+        };
         let tap = tap_desc.create_process_tap()?;
 
         let sub_tap = cf::DictionaryOf::with_keys_values(
@@ -86,7 +166,41 @@ impl SpeakerInput {
             ],
         );
 
-        Ok(Self { tap, agg_desc })
+        Ok(Self {
+            tap,
+            agg_desc,
+            output_uid: output_uid.to_string(),
+            on_device_event: None,
+            channel_mode: ChannelMode::default(),
+        })
+    }
+
+    pub fn set_device_event_callback(&mut self, callback: DeviceEventCallback) {
+        self.on_device_event = Some(callback);
+    }
+
+    pub fn set_channel_mode(&mut self, mode: ChannelMode) {
+        self.channel_mode = mode;
+    }
+
+    // The process tap always follows the default output device, so that is the only entry
+    pub fn list_devices() -> Result<Vec<AudioDevice>> {
+        let output_device = ca::System::default_output_device()?;
+        let id = output_device.uid()?.to_string();
+        let name = output_device
+            .name()
+            .map(|n| n.to_string())
+            .unwrap_or_else(|_| "System Audio".to_string());
+        let sample_rate = output_device.nominal_sample_rate().unwrap_or(48_000.0) as u32;
+
+        Ok(vec![AudioDevice {
+            id,
+            name,
+            kind: AudioDeviceKind::Loopback,
+            is_default: true,
+            channels: 1, // Mono global tap
+            sample_rate,
+        }])
     }
 
     fn start_device(
@@ -111,7 +225,22 @@ impl SpeakerInput {
                 Ordering::Release,
             );
 
-            if let Some(view) =
+            if ctx.channels > 1 {
+                // Stereo tap delivers one interleaved buffer
+                let first_buffer = &input_data.buffers[0];
+                let float_count =
+                    first_buffer.data_bytes_size as usize / std::mem::size_of::<f32>();
+
+                if float_count > 0 && !first_buffer.data.is_null() {
+                    let data = unsafe {
+                        std::slice::from_raw_parts(first_buffer.data as *const f32, float_count)
+                    };
+                    let mut mixed = std::mem::take(&mut ctx.mixed);
+                    ctx.channel_mode.apply_into(data, ctx.channels, &mut mixed);
+                    process_audio_data(ctx, &mixed);
+                    ctx.mixed = mixed;
+                }
+            } else if let Some(view) =
                 av::AudioPcmBuf::with_buf_list_no_copy(&ctx.format, input_data, None)
             {
                 if let Some(data) = view.data_f32_at(0) {
@@ -140,50 +269,96 @@ impl SpeakerInput {
         Ok(started_device)
     }
 
-    pub fn stream(self) -> SpeakerStream {
+    pub fn stream(mut self) -> SpeakerStream {
+        // Anything other than a plain mixdown needs the stereo tap
+        if self.channel_mode.needs_native_channels() {
+            match SpeakerInput::with_tap(true) {
+                Ok(stereo) => {
+                    self.tap = stereo.tap;
+                    self.agg_desc = stereo.agg_desc;
+                }
+                Err(e) => eprintln!("Stereo tap unavailable, using mono mixdown: {}", e),
+            }
+        }
+
         let asbd = self.tap.asbd().unwrap();
+        let channels = (asbd.channels_per_frame as usize).max(1);
 
         let format = av::AudioFormat::with_asbd(&asbd).unwrap();
 
-        let buffer_size = 1024 * 128;
-        let rb = HeapRb::<f32>::new(buffer_size);
-        let (producer, consumer) = rb.split();
-
-        let waker_state = Arc::new(Mutex::new(WakerState {
-            waker: None,
-            has_data: false,
-        }));
+        let (producer, consumer) = sample_queue(QUEUE_CAPACITY);
 
         let current_sample_rate = Arc::new(AtomicU32::new(asbd.sample_rate as u32));
 
         let mut ctx = Box::new(Ctx {
             format,
             producer,
-            waker_state: waker_state.clone(),
             current_sample_rate: current_sample_rate.clone(),
             consecutive_drops: Arc::new(AtomicU32::new(0)),
-            should_terminate: Arc::new(AtomicBool::new(false)),
+            channels,
+            channel_mode: self.channel_mode,
+            mixed: Vec::new(),
         });
 
         let device = self.start_device(&mut ctx).unwrap();
 
+        // The aggregate device is pinned to the output it was built on, so watch the default
+        let device_changed = Arc::new(AtomicBool::new(false));
+        spawn_default_device_watcher(
+            self.output_uid.clone(),
+            device_changed.clone(),
+            consumer.handle().clone(),
+        );
+
+        let device_name = ca::System::default_output_device()
+            .and_then(|d| d.name())
+            .map(|n| n.to_string())
+            .unwrap_or_else(|_| self.output_uid.clone());
+        consumer.handle().set_source(&device_name);
+
         SpeakerStream {
-            consumer,
-            _device: device,
+            queue: consumer,
+            _device: Some(device),
             _ctx: ctx,
             _tap: self.tap,
-            waker_state,
             current_sample_rate,
+            device_changed,
+            on_device_event: self.on_device_event,
         }
     }
 }
 
+// Flags the stream (and wakes the consumer) when the default output device changes
+fn spawn_default_device_watcher(
+    initial_uid: String,
+    device_changed: Arc<AtomicBool>,
+    queue: QueueHandle,
+) {
+    thread::spawn(move || {
+        let mut current_uid = initial_uid;
+
+        while !queue.is_closed() {
+            thread::sleep(DEFAULT_DEVICE_POLL_INTERVAL);
+
+            let uid = match ca::System::default_output_device().and_then(|d| d.uid()) {
+                Ok(uid) => uid.to_string(),
+                Err(_) => continue,
+            };
+
+            if uid != current_uid {
+                current_uid = uid;
+                device_changed.store(true, Ordering::Release);
+                queue.wake();
+            }
+        }
+    });
+}
+
 fn process_audio_data(ctx: &mut Ctx, data: &[f32]) {
-    let buffer_size = data.len();
-    let pushed = ctx.producer.push_slice(data);
+    // Drops are counted by the queue; a long run of them means the consumer is gone
+    let dropped = ctx.producer.push(data);
 
-    // Consistent buffer overflow handling
-    if pushed < buffer_size {
+    if dropped > 0 {
         let consecutive = ctx.consecutive_drops.fetch_add(1, Ordering::AcqRel) + 1;
 
         // Only terminate after many consecutive drops (prevents temporary spikes from killing stream)
@@ -193,28 +368,15 @@ fn process_audio_data(ctx: &mut Ctx, data: &[f32]) {
 
         if consecutive > 50 {
             eprintln!("Critical: Audio buffer overflow - capture stopping");
-            ctx.should_terminate.store(true, Ordering::Release);
-            return;
+            ctx.producer
+                .handle()
+                .record_error("Audio buffer overflow - capture stopped");
+            ctx.producer.handle().close();
         }
     } else {
         // Success - reset consecutive drops counter
         ctx.consecutive_drops.store(0, Ordering::Release);
     }
-
-    // Wake up consumer if we have new data
-    let should_wake = {
-        let mut waker_state = ctx.waker_state.lock().unwrap();
-        if !waker_state.has_data {
-            waker_state.has_data = true;
-            waker_state.waker.take()
-        } else {
-            None
-        }
-    };
-
-    if let Some(waker) = should_wake {
-        waker.wake();
-    }
 }
 
 impl Stream for SpeakerStream {
@@ -224,29 +386,21 @@ impl Stream for SpeakerStream {
         mut self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> Poll<Option<Self::Item>> {
-        if let Some(sample) = self.consumer.try_pop() {
-            return Poll::Ready(Some(sample));
-        }
-
-        if self._ctx.should_terminate.load(Ordering::Acquire) {
-            return match self.consumer.try_pop() {
-                Some(sample) => Poll::Ready(Some(sample)),
-                None => Poll::Ready(None),
-            };
-        }
-
-        {
-            let mut state = self.waker_state.lock().unwrap();
-            state.has_data = false;
-            state.waker = Some(cx.waker().clone());
-        }
-
-        Poll::Pending
+        self.follow_device_change();
+        self.queue.poll_sample(cx)
     }
 }
 
 impl Drop for SpeakerStream {
     fn drop(&mut self) {
-        self._ctx.should_terminate.store(true, Ordering::Release);
+        self.queue.handle().close();
+
+        let stats = self.queue.handle().stats();
+        if stats.dropped > 0 {
+            eprintln!(
+                "System audio capture dropped {} of {} samples ({} overflows)",
+                stats.dropped, stats.received, stats.overflows
+            );
+        }
     }
 }
@@ -0,0 +1,250 @@
+// Meetwings microphone input and stream (cpal, all platforms)
+use anyhow::{anyhow, Result};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, Sample, SampleFormat, SizedSample, StreamConfig};
+use futures_util::Stream;
+use std::sync::mpsc;
+use std::task::Poll;
+use std::thread;
+use std::time::Duration;
+use tracing::{error, warn};
+
+use super::frames::{AudioFrame, FrameSource, Frames};
+use super::queue::{sample_queue, QueueHandle, SampleConsumer, SampleProducer, QUEUE_CAPACITY};
+use super::{AudioDevice, AudioDeviceKind};
+
+const DEFAULT_SAMPLE_RATE: u32 = 44_100; // Listed for devices without a default config
+
+// An opened microphone; capture starts in new() so a missing or failing device is an error
+pub struct MicInput {
+    stream: MicStream,
+}
+
+impl MicInput {
+    // For the microphone, device_id is the cpal input device name
+    pub fn new(device_id: Option<String>) -> Result<Self> {
+        let (producer, consumer) = sample_queue(QUEUE_CAPACITY);
+        let (init_tx, init_rx) = mpsc::channel();
+
+        // cpal streams are not Send on every host, so the stream lives on its own thread
+        let capture_thread = thread::spawn(move || {
+            if let Err(e) = MicStream::capture_audio_loop(producer, device_id.as_deref(), init_tx) {
+                error!("Microphone capture loop failed: {}", e);
+            }
+        });
+
+        let init = init_rx.recv_timeout(Duration::from_secs(5));
+        let mut stream = MicStream {
+            queue: consumer,
+            capture_thread: Some(capture_thread),
+            sample_rate: 0,
+        };
+
+        // Dropping the stream closes the queue and joins the capture thread
+        match init {
+            Ok(Ok(sample_rate)) => {
+                stream.sample_rate = sample_rate;
+                Ok(Self { stream })
+            }
+            Ok(Err(e)) => Err(anyhow!("Microphone initialization failed: {}", e)),
+            Err(_) => Err(anyhow!("Microphone initialization timeout")),
+        }
+    }
+
+    // Lists input devices on the default cpal host
+    pub fn list_devices() -> Result<Vec<AudioDevice>> {
+        let host = cpal::default_host();
+        let default_name = host.default_input_device().and_then(|d| d.name().ok());
+
+        let mut devices = Vec::new();
+        for device in host.input_devices()? {
+            let Ok(name) = device.name() else {
+                continue;
+            };
+            let (channels, sample_rate) = device
+                .default_input_config()
+                .map(|config| (config.channels(), config.sample_rate().0))
+                .unwrap_or((1, DEFAULT_SAMPLE_RATE));
+
+            devices.push(AudioDevice {
+                is_default: default_name.as_deref() == Some(name.as_str()),
+                id: name.clone(),
+                name,
+                kind: AudioDeviceKind::Input,
+                channels,
+                sample_rate,
+            });
+        }
+
+        Ok(devices)
+    }
+
+    // The stream opened by new()
+    pub fn stream(self) -> MicStream {
+        self.stream
+    }
+}
+
+pub struct MicStream {
+    queue: SampleConsumer,
+    capture_thread: Option<thread::JoinHandle<()>>,
+    sample_rate: u32,
+}
+
+impl MicStream {
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn backend(&self) -> &'static str {
+        "cpal"
+    }
+
+    pub fn queue(&self) -> &QueueHandle {
+        self.queue.handle()
+    }
+
+    // Fixed-size frames with capture timestamps instead of single samples
+    pub fn frames(self, frame_size: usize) -> Frames<Self> {
+        Frames::new(self, frame_size)
+    }
+
+    fn capture_audio_loop(
+        producer: SampleProducer,
+        device_name: Option<&str>,
+        init_tx: mpsc::Sender<Result<u32>>,
+    ) -> Result<()> {
+        let queue = producer.handle().clone();
+
+        let init_result = (|| -> Result<_> {
+            let host = cpal::default_host();
+            let device = match device_name {
+                Some(name) => host
+                    .input_devices()?
+                    .find(|d| d.name().map(|n| n == name).unwrap_or(false))
+                    .ok_or_else(|| anyhow!("Input device not found: {}", name))?,
+                None => host
+                    .default_input_device()
+                    .ok_or_else(|| anyhow!("No default input device available"))?,
+            };
+
+            queue.set_source(&device.name().unwrap_or_default());
+
+            let supported = device.default_input_config()?;
+            let sample_format = supported.sample_format();
+            let config: StreamConfig = supported.into();
+            let sample_rate = config.sample_rate.0;
+
+            let stream = match sample_format {
+                SampleFormat::F32 => build_input_stream::<f32>(&device, &config, producer)?,
+                SampleFormat::I16 => build_input_stream::<i16>(&device, &config, producer)?,
+                SampleFormat::U16 => build_input_stream::<u16>(&device, &config, producer)?,
+                SampleFormat::I32 => build_input_stream::<i32>(&device, &config, producer)?,
+                other => return Err(anyhow!("Unsupported input sample format: {}", other)),
+            };
+
+            stream.play()?;
+
+            Ok((stream, sample_rate))
+        })();
+
+        match init_result {
+            Ok((stream, sample_rate)) => {
+                let _ = init_tx.send(Ok(sample_rate));
+
+                // Keep the stream alive until shutdown is requested
+                loop {
+                    if queue.is_closed() {
+                        break;
+                    }
+                    thread::sleep(Duration::from_millis(50));
+                }
+
+                drop(stream);
+            }
+            Err(e) => {
+                let _ = init_tx.send(Err(e));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn build_input_stream<T>(
+    device: &cpal::Device,
+    config: &StreamConfig,
+    mut producer: SampleProducer,
+) -> Result<cpal::Stream>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let channels = config.channels.max(1) as usize;
+    let errors = producer.handle().clone();
+    let mut samples: Vec<f32> = Vec::new(); // Reused; grows to the callback size once
+
+    let stream = device.build_input_stream(
+        config,
+        move |data: &[T], _: &cpal::InputCallbackInfo| {
+            // Downmix interleaved frames to mono
+            samples.clear();
+            samples.extend(data.chunks_exact(channels).map(|frame| {
+                frame.iter().map(|&s| s.to_sample::<f32>()).sum::<f32>() / channels as f32
+            }));
+
+            // Overflow is counted by the queue (see QueueStats)
+            producer.push(&samples);
+        },
+        move |e| {
+            error!("Microphone stream error: {}", e);
+            errors.record_error(&e);
+        },
+        None,
+    )?;
+
+    Ok(stream)
+}
+
+impl Drop for MicStream {
+    fn drop(&mut self) {
+        self.queue.handle().close();
+        if let Some(thread) = self.capture_thread.take() {
+            let _ = thread.join();
+        }
+
+        let stats = self.queue.handle().stats();
+        if stats.dropped > 0 {
+            warn!(
+                "Microphone capture dropped {} of {} samples ({} overflows)",
+                stats.dropped, stats.received, stats.overflows
+            );
+        }
+    }
+}
+
+impl FrameSource for MicStream {
+    fn poll_frame(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+        frame_size: usize,
+    ) -> Poll<Option<AudioFrame>> {
+        self.queue.poll_frame(cx, frame_size, self.sample_rate)
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+}
+
+// Stream of f32 audio samples from the microphone
+impl Stream for MicStream {
+    type Item = f32;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.queue.poll_sample(cx)
+    }
+}
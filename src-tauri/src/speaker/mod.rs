@@ -1,6 +1,10 @@
 use anyhow::Result;
+use file::{FileInput, FileStream};
+use frames::FrameSource;
 use futures_util::Stream;
+use serde::Serialize;
 use std::pin::Pin;
+use std::sync::Arc;
 
 #[cfg(target_os = "macos")]
 mod macos;
@@ -15,64 +19,170 @@ use windows::{SpeakerInput as PlatformSpeakerInput, SpeakerStream as PlatformSpe
 #[cfg(target_os = "linux")]
 mod linux;
 #[cfg(target_os = "linux")]
+pub use linux::unload_stale_routes;
+#[cfg(target_os = "linux")]
 use linux::{SpeakerInput as PlatformSpeakerInput, SpeakerStream as PlatformSpeakerStream};
 
 mod commands;
+mod denoise;
+mod diagnostics;
+mod downmix;
+mod encode;
+mod file;
+mod flac;
+mod frames;
+mod import;
+mod mic;
+#[cfg(feature = "opus")]
+mod opus;
+mod queue;
+mod realtime;
+mod recording;
+mod resample;
+mod segments;
+mod spectrum;
+#[cfg(test)]
+mod tests;
 
 // Re-export commands for tauri handler
 pub use commands::*;
+pub use diagnostics::{AudioDiagnostics, CaptureMonitor, NoiseEstimate};
+pub use downmix::{ChannelMode, Downmix};
+pub use encode::AudioEncoding;
+pub use frames::{AudioFrame, Frames};
+pub use mic::{MicInput, MicStream};
+pub use queue::QueueHandle;
+pub use recording::MeetingRecording;
+pub use resample::resample;
+pub use segments::SegmentStore;
+
+// What a listed device captures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioDeviceKind {
+    Monitor,     // PulseAudio monitor source (Linux)
+    Loopback,    // Render endpoint captured in loopback (Windows/macOS)
+    Input,       // Microphone / line-in
+    Application, // One program's playback streams (Linux)
+}
+
+// Capture device as reported by list_audio_devices
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioDevice {
+    pub id: String, // Pass back as device_id / mic_device_id
+    pub name: String,
+    pub kind: AudioDeviceKind,
+    pub is_default: bool,
+    pub channels: u16,
+    pub sample_rate: u32,
+}
+
+// Hot-plug notifications raised from the platform capture threads
+#[derive(Debug, Clone)]
+pub enum DeviceEvent {
+    // Capture was reopened on this device (new default or recovery)
+    Changed { device: String },
+    // The device being captured failed or disappeared
+    Lost { device: String, error: String },
+}
+
+pub type DeviceEventCallback = Arc<dyn Fn(DeviceEvent) + Send + Sync>;
 
 // Meetwings speaker input and stream
 pub struct SpeakerInput {
+    inner: InputKind,
+}
+
+// Real capture device, or a file / generator selected by its device ID (see file.rs)
+enum InputKind {
     #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
-    inner: PlatformSpeakerInput,
+    Platform(PlatformSpeakerInput),
+    File(FileInput),
 }
 
 impl SpeakerInput {
     // Creates a new speaker input. Fails on unsupported platforms.
-    #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
     pub fn new() -> Result<Self> {
-        let inner = PlatformSpeakerInput::new(None)?;
-        Ok(Self { inner })
+        Self::new_with_device(None)
     }
 
-    // Creates a new speaker input with a specific device ID
-    #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+    // Creates a new speaker input with a specific device ID.
+    // `file://` and `generator://` IDs work on every platform.
     pub fn new_with_device(device_id: Option<String>) -> Result<Self> {
-        let inner = PlatformSpeakerInput::new(device_id)?;
-        Ok(Self { inner })
+        if let Some(id) = device_id.as_deref().filter(|id| FileInput::handles(id)) {
+            let inner = InputKind::File(FileInput::new(id)?);
+            return Ok(Self { inner });
+        }
+
+        #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+        {
+            let inner = InputKind::Platform(PlatformSpeakerInput::new(device_id)?);
+            Ok(Self { inner })
+        }
+
+        #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+        {
+            Err(anyhow::anyhow!(
+                "System audio capture is not supported on this platform"
+            ))
+        }
     }
 
-    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
-    pub fn new() -> Result<Self> {
-        Err(anyhow::anyhow!(
-            "SpeakerInput::new is not supported on this platform"
-        ))
+    // Lists system output capture devices (monitors / loopback endpoints)
+    #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+    pub fn list_devices() -> Result<Vec<AudioDevice>> {
+        PlatformSpeakerInput::list_devices()
     }
 
     #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
-    pub fn new_with_device(_device_id: Option<String>) -> Result<Self> {
-        Err(anyhow::anyhow!(
-            "SpeakerInput::new_with_device is not supported on this platform"
-        ))
+    pub fn list_devices() -> Result<Vec<AudioDevice>> {
+        Ok(Vec::new())
     }
 
-    // Starts the audio stream.
-    #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
-    pub fn stream(self) -> SpeakerStream {
-        let inner = self.inner.stream();
-        SpeakerStream { inner }
+    // Registers a callback for device loss / default-device changes during capture.
+    // File inputs never raise device events.
+    pub fn on_device_event(
+        mut self,
+        callback: impl Fn(DeviceEvent) + Send + Sync + 'static,
+    ) -> Self {
+        match &mut self.inner {
+            #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+            InputKind::Platform(input) => input.set_device_event_callback(Arc::new(callback)),
+            InputKind::File(_) => {}
+        }
+        self
     }
 
-    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    // Selects mono downmix strategy or interleaved multichannel output (default: averaged mono)
+    pub fn with_channel_mode(mut self, mode: ChannelMode) -> Self {
+        match &mut self.inner {
+            #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+            InputKind::Platform(input) => input.set_channel_mode(mode),
+            InputKind::File(input) => input.set_channel_mode(mode),
+        }
+        self
+    }
+
+    // Starts the audio stream.
     pub fn stream(self) -> SpeakerStream {
-        unimplemented!("SpeakerInput::stream is not supported on this platform")
+        let inner = match self.inner {
+            #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+            InputKind::Platform(input) => StreamKind::Platform(input.stream()),
+            InputKind::File(input) => StreamKind::File(input.stream()),
+        };
+        SpeakerStream { inner }
     }
 }
 
 // Stream of f32 audio samples from the speaker.
 pub struct SpeakerStream {
-    inner: PlatformSpeakerStream,
+    inner: StreamKind,
+}
+
+enum StreamKind {
+    #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+    Platform(PlatformSpeakerStream),
+    File(FileStream),
 }
 
 impl Stream for SpeakerStream {
@@ -82,25 +192,72 @@ impl Stream for SpeakerStream {
         mut self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<Option<Self::Item>> {
-        #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
-        {
-            Pin::new(&mut self.inner).poll_next(cx)
+        match &mut self.inner {
+            #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+            StreamKind::Platform(stream) => Pin::new(stream).poll_next(cx),
+            StreamKind::File(stream) => stream.poll_sample(cx),
         }
+    }
+}
 
-        #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
-        {
-            std::task::Poll::Pending
+impl FrameSource for SpeakerStream {
+    fn poll_frame(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+        frame_size: usize,
+    ) -> std::task::Poll<Option<AudioFrame>> {
+        match &mut self.inner {
+            #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+            StreamKind::Platform(stream) => stream.poll_frame(cx, frame_size),
+            StreamKind::File(stream) => stream.poll_frame(cx, frame_size),
         }
     }
+
+    fn sample_rate(&self) -> u32 {
+        SpeakerStream::sample_rate(self)
+    }
 }
 
 impl SpeakerStream {
-    // Gets the sample rate (e.g., 16000 Hz on stub, variable on real impls).
+    // Fixed-size frames with capture timestamps instead of single samples
+    pub fn frames(self, frame_size: usize) -> Frames<Self> {
+        Frames::new(self, frame_size)
+    }
+
+    // Gets the sample rate (the device's native rate, or the file's).
     pub fn sample_rate(&self) -> u32 {
-        #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
-        return self.inner.sample_rate();
+        match &self.inner {
+            #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+            StreamKind::Platform(stream) => stream.sample_rate(),
+            StreamKind::File(stream) => stream.sample_rate(),
+        }
+    }
 
-        #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
-        0
+    // Samples per frame: 1 unless the stream was opened with ChannelMode::Interleaved
+    #[allow(dead_code)] // For consumers that separate channels themselves
+    pub fn channels(&self) -> usize {
+        match &self.inner {
+            #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+            StreamKind::Platform(stream) => stream.channels(),
+            StreamKind::File(stream) => stream.channels(),
+        }
+    }
+
+    // Handle to the capture queue's counters (received / dropped / queued samples)
+    pub fn queue(&self) -> QueueHandle {
+        match &self.inner {
+            #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+            StreamKind::Platform(stream) => stream.queue().clone(),
+            StreamKind::File(stream) => stream.queue().clone(),
+        }
+    }
+
+    // Name of the capture backend ("pulseaudio", "pipewire", "wasapi", "coreaudio", "file")
+    pub fn backend(&self) -> &'static str {
+        match &self.inner {
+            #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+            StreamKind::Platform(stream) => stream.backend(),
+            StreamKind::File(stream) => stream.backend(),
+        }
     }
 }
@@ -0,0 +1,90 @@
+// Meetwings Ogg Opus encoder (RFC 7845), mono VoIP mode. Needs the `opus` cargo feature.
+use audiopus::{coder::Encoder, Application, Bitrate, Channels, SampleRate};
+use ogg::writing::{PacketWriteEndInfo, PacketWriter};
+
+use super::resample::resample;
+
+const BITRATE: i32 = 24_000; // Transparent enough for speech recognition
+const FRAME_MS: u32 = 20;
+const MAX_PACKET_SIZE: usize = 4000; // Recommended by libopus for one frame
+const GRANULE_RATE: u64 = 48_000; // Ogg Opus granule positions are always at 48 kHz
+const STREAM_SERIAL: u32 = 1;
+const VENDOR: &str = "meetwings";
+
+pub fn encode_ogg_opus(samples: &[f32], sample_rate: u32) -> Result<Vec<u8>, String> {
+    // Opus only runs at a few rates; use the closest one that keeps the bandwidth
+    let (opus_rate, rate) = match sample_rate {
+        0..=8_000 => (SampleRate::Hz8000, 8_000),
+        8_001..=12_000 => (SampleRate::Hz12000, 12_000),
+        12_001..=16_000 => (SampleRate::Hz16000, 16_000),
+        16_001..=24_000 => (SampleRate::Hz24000, 24_000),
+        _ => (SampleRate::Hz48000, 48_000),
+    };
+    let resampled;
+    let samples = if rate == sample_rate {
+        samples
+    } else {
+        resampled = resample(samples, sample_rate, rate);
+        &resampled
+    };
+
+    let mut encoder = Encoder::new(opus_rate, Channels::Mono, Application::Voip)
+        .map_err(|e| format!("Failed to create Opus encoder: {}", e))?;
+    encoder
+        .set_bitrate(Bitrate::BitsPerSecond(BITRATE))
+        .map_err(|e| format!("Failed to configure Opus encoder: {}", e))?;
+    let lookahead = encoder
+        .lookahead()
+        .map_err(|e| format!("Failed to configure Opus encoder: {}", e))?;
+
+    let to_granule = |samples: u64| samples * GRANULE_RATE / rate as u64;
+    let pre_skip = to_granule(lookahead as u64);
+    let end_granule = pre_skip + to_granule(samples.len() as u64);
+
+    let mut writer = PacketWriter::new(Vec::new());
+    let mut write = |packet: Vec<u8>, info: PacketWriteEndInfo, granule: u64| {
+        writer
+            .write_packet(packet.into_boxed_slice(), STREAM_SERIAL, info, granule)
+            .map_err(|e| format!("Failed to write Ogg page: {}", e))
+    };
+
+    // Identification and comment headers each get their own page
+    let mut head = b"OpusHead".to_vec();
+    head.push(1); // Version
+    head.push(1); // Channels
+    head.extend_from_slice(&(pre_skip as u16).to_le_bytes());
+    head.extend_from_slice(&sample_rate.to_le_bytes()); // Original input rate
+    head.extend_from_slice(&0i16.to_le_bytes()); // Output gain
+    head.push(0); // Mono/stereo channel mapping
+    write(head, PacketWriteEndInfo::EndPage, 0)?;
+
+    let mut tags = b"OpusTags".to_vec();
+    tags.extend_from_slice(&(VENDOR.len() as u32).to_le_bytes());
+    tags.extend_from_slice(VENDOR.as_bytes());
+    tags.extend_from_slice(&0u32.to_le_bytes()); // No user comments
+    write(tags, PacketWriteEndInfo::EndPage, 0)?;
+
+    // Pad with the encoder delay so the tail is flushed; the final granule trims it
+    let frame_size = (rate * FRAME_MS / 1000) as usize;
+    let padded_len = (samples.len() + lookahead as usize).div_ceil(frame_size) * frame_size;
+    let mut padded = samples.to_vec();
+    padded.resize(padded_len.max(frame_size), 0.0);
+
+    let frames = padded.len() / frame_size;
+    let mut packet = vec![0u8; MAX_PACKET_SIZE];
+    for (index, frame) in padded.chunks(frame_size).enumerate() {
+        let len = encoder
+            .encode_float(frame, &mut packet)
+            .map_err(|e| format!("Opus encoding failed: {}", e))?;
+
+        let (info, granule) = if index + 1 == frames {
+            (PacketWriteEndInfo::EndStream, end_granule)
+        } else {
+            let granule = to_granule(((index + 1) * frame_size) as u64);
+            (PacketWriteEndInfo::NormalPacket, granule.min(end_granule))
+        };
+        write(packet[..len].to_vec(), info, granule)?;
+    }
+
+    Ok(writer.into_inner())
+}
@@ -0,0 +1,414 @@
+// Meetwings sample queue: SPSC ring between a capture thread and its stream, plus the
+// capture's health (counters, current source, last backend error)
+use futures_util::task::AtomicWaker;
+use ringbuf::{
+    traits::{Consumer, Producer, Split},
+    HeapCons, HeapProd, HeapRb,
+};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::frames::AudioFrame;
+
+pub const QUEUE_CAPACITY: usize = 131072; // 128K samples, shared by every capture backend
+const READ_BLOCK: usize = 1024; // Samples moved out of the ring per refill
+
+struct Shared {
+    waker: AtomicWaker,
+    closed: AtomicBool,
+    received: AtomicU64,
+    dropped: AtomicU64,
+    overflows: AtomicU64,
+    consumed: AtomicU64,
+    skip: AtomicUsize, // Oldest samples the consumer still has to discard after an overflow
+    last_push_us: AtomicU64, // Unix time of the most recent push
+    source: Mutex<Option<String>>,
+    last_error: Mutex<Option<String>>,
+}
+
+// Counters snapshot; `dropped` samples were discarded because the consumer fell behind
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct QueueStats {
+    pub received: u64,
+    pub dropped: u64,
+    pub overflows: u64, // Separate overflow episodes (runs of full-queue pushes)
+    pub queued: u64,
+    pub capacity: usize,
+    pub last_sample_age_ms: Option<u64>, // None until the first push
+}
+
+// Cloneable view of the queue's state, usable from any thread
+#[derive(Clone)]
+pub struct QueueHandle {
+    shared: Arc<Shared>,
+    capacity: usize,
+}
+
+impl QueueHandle {
+    pub fn stats(&self) -> QueueStats {
+        let received = self.shared.received.load(Ordering::Relaxed);
+        let dropped = self.shared.dropped.load(Ordering::Relaxed);
+        let consumed = self.shared.consumed.load(Ordering::Relaxed);
+        let last_push_us = self.shared.last_push_us.load(Ordering::Acquire);
+
+        QueueStats {
+            received,
+            dropped,
+            overflows: self.shared.overflows.load(Ordering::Relaxed),
+            queued: received.saturating_sub(dropped).saturating_sub(consumed),
+            capacity: self.capacity,
+            last_sample_age_ms: (last_push_us > 0)
+                .then(|| unix_micros().saturating_sub(last_push_us) / 1000),
+        }
+    }
+
+    // Device currently being captured, as reported by the backend
+    pub fn source(&self) -> Option<String> {
+        self.shared.source.lock().unwrap().clone()
+    }
+
+    pub fn set_source(&self, source: &str) {
+        *self.shared.source.lock().unwrap() = Some(source.to_string());
+    }
+
+    pub fn last_error(&self) -> Option<String> {
+        self.shared.last_error.lock().unwrap().clone()
+    }
+
+    // Kept for diagnostics; not for the audio callback path
+    pub fn record_error(&self, error: impl ToString) {
+        *self.shared.last_error.lock().unwrap() = Some(error.to_string());
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.shared.closed.load(Ordering::Acquire)
+    }
+
+    // Ends the stream once the remaining samples are read
+    pub fn close(&self) {
+        self.shared.closed.store(true, Ordering::Release);
+        self.wake();
+    }
+
+    // Wakes the consumer without new samples (e.g. to act on a device change)
+    pub fn wake(&self) {
+        self.shared.waker.wake();
+    }
+}
+
+pub fn sample_queue(capacity: usize) -> (SampleProducer, SampleConsumer) {
+    let (producer, consumer) = HeapRb::<f32>::new(capacity).split();
+    let handle = QueueHandle {
+        shared: Arc::new(Shared {
+            waker: AtomicWaker::new(),
+            closed: AtomicBool::new(false),
+            received: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            overflows: AtomicU64::new(0),
+            consumed: AtomicU64::new(0),
+            skip: AtomicUsize::new(0),
+            last_push_us: AtomicU64::new(0),
+            source: Mutex::new(None),
+            last_error: Mutex::new(None),
+        }),
+        capacity,
+    };
+
+    (
+        SampleProducer {
+            producer,
+            handle: handle.clone(),
+            overflowing: false,
+        },
+        SampleConsumer {
+            consumer,
+            handle,
+            block: vec![0.0; READ_BLOCK].into_boxed_slice(),
+            block_len: 0,
+            block_pos: 0,
+            pending: Vec::new(),
+        },
+    )
+}
+
+// Capture side. Never blocks or allocates, so it is safe in real-time audio callbacks.
+pub struct SampleProducer {
+    producer: HeapProd<f32>,
+    handle: QueueHandle,
+    overflowing: bool,
+}
+
+impl SampleProducer {
+    // Pushes a block and wakes the consumer. When full, the samples that don't fit are dropped
+    // and the consumer is told to skip as many of the oldest, so it catches up with the
+    // capture; returns how many samples were dropped here.
+    pub fn push(&mut self, samples: &[f32]) -> usize {
+        if samples.is_empty() {
+            return 0;
+        }
+
+        let shared = &self.handle.shared;
+        shared
+            .received
+            .fetch_add(samples.len() as u64, Ordering::Relaxed);
+
+        let dropped = samples.len() - self.producer.push_slice(samples);
+        if dropped > 0 {
+            shared.dropped.fetch_add(dropped as u64, Ordering::Relaxed);
+            shared.skip.fetch_add(dropped, Ordering::Release);
+            if !self.overflowing {
+                self.overflowing = true;
+                shared.overflows.fetch_add(1, Ordering::Relaxed);
+            }
+        } else {
+            self.overflowing = false;
+        }
+
+        shared.last_push_us.store(unix_micros(), Ordering::Release);
+        shared.waker.wake();
+        dropped
+    }
+
+    pub fn handle(&self) -> &QueueHandle {
+        &self.handle
+    }
+}
+
+// Stream side. Samples are taken from the ring a block at a time.
+pub struct SampleConsumer {
+    consumer: HeapCons<f32>,
+    handle: QueueHandle,
+    block: Box<[f32]>,
+    block_len: usize,
+    block_pos: usize,
+    pending: Vec<f32>, // Partially filled frame
+}
+
+impl SampleConsumer {
+    pub fn poll_sample(&mut self, cx: &mut Context<'_>) -> Poll<Option<f32>> {
+        if self.block_pos == self.block_len && !self.refill() {
+            self.handle.shared.waker.register(cx.waker());
+
+            // Re-check after registering so a push in between isn't missed
+            if !self.refill() {
+                if !self.handle.is_closed() {
+                    return Poll::Pending;
+                }
+                // Samples pushed just before close
+                if !self.refill() {
+                    return Poll::Ready(None);
+                }
+            }
+        }
+
+        let sample = self.block[self.block_pos];
+        self.block_pos += 1;
+        Poll::Ready(Some(sample))
+    }
+
+    // Yields `frame_size` samples at a time, popped straight from the ring
+    pub fn poll_frame(
+        &mut self,
+        cx: &mut Context<'_>,
+        frame_size: usize,
+        sample_rate: u32,
+    ) -> Poll<Option<AudioFrame>> {
+        let frame_size = frame_size.max(1);
+
+        if !self.fill_frame(frame_size) {
+            self.handle.shared.waker.register(cx.waker());
+
+            if !self.fill_frame(frame_size) {
+                if !self.handle.is_closed() {
+                    return Poll::Pending;
+                }
+                // Flush whatever is left as a short final frame
+                self.fill_frame(frame_size);
+                if self.pending.is_empty() {
+                    return Poll::Ready(None);
+                }
+            }
+        }
+
+        Poll::Ready(Some(self.take_frame(sample_rate)))
+    }
+
+    pub fn handle(&self) -> &QueueHandle {
+        &self.handle
+    }
+
+    fn fill_frame(&mut self, frame_size: usize) -> bool {
+        // Samples already taken from the ring by poll_sample come first
+        let buffered = (frame_size - self.pending.len()).min(self.block_len - self.block_pos);
+        self.pending
+            .extend_from_slice(&self.block[self.block_pos..self.block_pos + buffered]);
+        self.block_pos += buffered;
+
+        let filled = self.pending.len();
+        if filled < frame_size {
+            self.pending.resize(frame_size, 0.0);
+            self.skip_ahead();
+            let read = self.consumer.pop_slice(&mut self.pending[filled..]);
+            self.pending.truncate(filled + read);
+            self.handle
+                .shared
+                .consumed
+                .fetch_add(read as u64, Ordering::Relaxed);
+        }
+
+        self.pending.len() == frame_size
+    }
+
+    fn take_frame(&mut self, sample_rate: u32) -> AudioFrame {
+        let capacity = self.pending.capacity();
+        let samples = std::mem::replace(&mut self.pending, Vec::with_capacity(capacity));
+
+        // Everything captured after this frame is still queued, so count back from the last push
+        let later = self.handle.stats().queued + (self.block_len - self.block_pos) as u64;
+        let age_us = (later + samples.len() as u64) * 1_000_000 / sample_rate.max(1) as u64;
+        let last_push_us = self.handle.shared.last_push_us.load(Ordering::Acquire);
+
+        AudioFrame {
+            samples,
+            captured_at_ms: last_push_us.saturating_sub(age_us) / 1000,
+        }
+    }
+
+    // Discards the oldest queued samples the producer asked to skip when it overflowed
+    fn skip_ahead(&mut self) {
+        let skip = self.handle.shared.skip.swap(0, Ordering::Acquire);
+        if skip > 0 {
+            let skipped = self.consumer.skip(skip);
+            self.handle
+                .shared
+                .dropped
+                .fetch_add(skipped as u64, Ordering::Relaxed);
+        }
+    }
+
+    fn refill(&mut self) -> bool {
+        self.skip_ahead();
+        let read = self.consumer.pop_slice(&mut self.block);
+        self.handle
+            .shared
+            .consumed
+            .fetch_add(read as u64, Ordering::Relaxed);
+        self.block_len = read;
+        self.block_pos = 0;
+        read > 0
+    }
+}
+
+fn unix_micros() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_micros() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::task::{Wake, Waker};
+
+    struct CountingWaker(AtomicUsize);
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    fn counting_waker() -> (Arc<CountingWaker>, Waker) {
+        let counter = Arc::new(CountingWaker(AtomicUsize::new(0)));
+        (counter.clone(), Waker::from(counter))
+    }
+
+    fn drain(consumer: &mut SampleConsumer, cx: &mut Context<'_>) -> Vec<f32> {
+        let mut samples = Vec::new();
+        while let Poll::Ready(Some(sample)) = consumer.poll_sample(cx) {
+            samples.push(sample);
+        }
+        samples
+    }
+
+    #[test]
+    fn samples_come_out_in_push_order() {
+        let (mut producer, mut consumer) = sample_queue(64);
+        let (_, waker) = counting_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        producer.push(&[1.0, 2.0, 3.0]);
+        producer.push(&[4.0, 5.0]);
+        assert_eq!(drain(&mut consumer, &mut cx), [1.0, 2.0, 3.0, 4.0, 5.0]);
+
+        producer.push(&[6.0, 7.0, 8.0, 9.0]);
+        let frame = match consumer.poll_frame(&mut cx, 3, 16_000) {
+            Poll::Ready(Some(frame)) => frame,
+            other => panic!("expected a frame, got {:?}", other),
+        };
+        assert_eq!(frame.samples, [6.0, 7.0, 8.0]);
+        assert_eq!(drain(&mut consumer, &mut cx), [9.0]);
+
+        let stats = consumer.handle().stats();
+        assert_eq!((stats.received, stats.dropped, stats.queued), (9, 0, 0));
+    }
+
+    #[test]
+    fn overflow_skips_ahead_and_counts_episodes() {
+        let (mut producer, mut consumer) = sample_queue(8);
+        let (_, waker) = counting_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        assert_eq!(producer.push(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]), 0);
+        assert_eq!(producer.push(&[7.0, 8.0, 9.0, 10.0]), 2);
+
+        let stats = consumer.handle().stats();
+        assert_eq!((stats.received, stats.dropped, stats.overflows), (10, 2, 1));
+        assert_eq!(stats.queued, 8);
+
+        // The consumer discards as many of the oldest samples as were dropped
+        assert_eq!(
+            drain(&mut consumer, &mut cx),
+            [3.0, 4.0, 5.0, 6.0, 7.0, 8.0]
+        );
+        let stats = consumer.handle().stats();
+        assert_eq!((stats.dropped, stats.queued), (4, 0));
+
+        // Room again ends the episode; the next overflow is a new one
+        assert_eq!(producer.push(&[11.0, 12.0]), 0);
+        let block: Vec<f32> = (13..23).map(|n| n as f32).collect();
+        assert_eq!(producer.push(&block), 4);
+
+        let stats = consumer.handle().stats();
+        assert_eq!((stats.received, stats.dropped, stats.overflows), (22, 8, 2));
+        assert_eq!(drain(&mut consumer, &mut cx), [15.0, 16.0, 17.0, 18.0]);
+        assert_eq!(consumer.handle().stats().queued, 0);
+    }
+
+    #[test]
+    fn close_wakes_pending_poll_frame() {
+        let (mut producer, mut consumer) = sample_queue(64);
+        let (wakes, waker) = counting_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        producer.push(&[1.0, 2.0]);
+        assert!(consumer.poll_frame(&mut cx, 4, 16_000).is_pending());
+
+        producer.handle().close();
+        assert!(wakes.0.load(Ordering::SeqCst) > 0);
+
+        // The partial frame is flushed, then the stream ends
+        match consumer.poll_frame(&mut cx, 4, 16_000) {
+            Poll::Ready(Some(frame)) => assert_eq!(frame.samples, [1.0, 2.0]),
+            other => panic!("expected the final short frame, got {:?}", other),
+        }
+        assert!(matches!(
+            consumer.poll_frame(&mut cx, 4, 16_000),
+            Poll::Ready(None)
+        ));
+    }
+}
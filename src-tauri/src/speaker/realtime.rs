@@ -0,0 +1,579 @@
+// Meetwings realtime transcription: streams capture frames to a WebSocket STT service
+// (Deepgram, AssemblyAI, OpenAI realtime) and reports partial / final transcripts
+use base64::{engine::general_purpose::STANDARD as B64, Engine as _};
+use futures_util::{SinkExt, Stream, StreamExt};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio_tungstenite::tungstenite::{
+    self,
+    client::IntoClientRequest,
+    http::{HeaderName, HeaderValue},
+    protocol::frame::coding::CloseCode,
+    Message,
+};
+use tracing::warn;
+
+use super::frames::AudioFrame;
+use super::resample::Resampler;
+
+const SEND_INTERVAL_MS: u32 = 100; // Audio batched into each WebSocket message
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(5); // Wait for trailing finals after stop
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RealtimeProvider {
+    Deepgram,
+    AssemblyAi,
+    OpenAi,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RealtimeSttConfig {
+    pub provider: RealtimeProvider,
+    #[serde(skip)] // Filled from the saved settings; never sent over IPC
+    pub api_key: String,
+    pub url: Option<String>, // Overrides the provider endpoint (proxies, local mock servers)
+    pub model: Option<String>,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Transcript {
+    pub text: String,
+    pub is_final: bool,
+}
+
+impl RealtimeProvider {
+    // Batch STT provider (see stt.constants.ts) whose saved API key the realtime service uses
+    pub fn stt_provider_id(self) -> &'static str {
+        match self {
+            Self::Deepgram => "deepgram-stt",
+            Self::AssemblyAi => "assemblyai-diarization",
+            Self::OpenAi => "openai-whisper",
+        }
+    }
+
+    // PCM16 mono rate the service expects
+    fn sample_rate(self) -> u32 {
+        match self {
+            Self::OpenAi => 24_000,
+            Self::Deepgram | Self::AssemblyAi => 16_000,
+        }
+    }
+
+    fn default_url(self) -> &'static str {
+        match self {
+            Self::Deepgram => "wss://api.deepgram.com/v1/listen",
+            Self::AssemblyAi => "wss://streaming.assemblyai.com/v3/ws",
+            Self::OpenAi => "wss://api.openai.com/v1/realtime",
+        }
+    }
+
+    fn query(self, model: Option<&str>) -> Vec<(&'static str, String)> {
+        let rate = self.sample_rate().to_string();
+        let mut query = match self {
+            Self::Deepgram => vec![
+                ("encoding", "linear16".to_string()),
+                ("sample_rate", rate),
+                ("channels", "1".to_string()),
+                ("interim_results", "true".to_string()),
+            ],
+            Self::AssemblyAi => vec![("encoding", "pcm_s16le".to_string()), ("sample_rate", rate)],
+            Self::OpenAi => vec![("intent", "transcription".to_string())],
+        };
+        // OpenAI takes the model in the session update instead
+        if let (Some(model), false) = (model, self == Self::OpenAi) {
+            query.push(("model", model.to_string()));
+        }
+        query
+    }
+
+    fn auth_headers(self, api_key: &str) -> Vec<(&'static str, String)> {
+        match self {
+            Self::Deepgram => vec![("Authorization", format!("Token {}", api_key))],
+            Self::AssemblyAi => vec![("Authorization", api_key.to_string())],
+            Self::OpenAi => vec![
+                ("Authorization", format!("Bearer {}", api_key)),
+                ("OpenAI-Beta", "realtime=v1".to_string()),
+            ],
+        }
+    }
+
+    // Sent once after connecting
+    fn session_message(self, model: Option<&str>) -> Option<Message> {
+        match self {
+            Self::OpenAi => Some(Message::Text(
+                json!({
+                    "type": "transcription_session.update",
+                    "session": {
+                        "input_audio_format": "pcm16",
+                        "input_audio_transcription": {
+                            "model": model.unwrap_or("gpt-4o-mini-transcribe"),
+                        },
+                        "turn_detection": { "type": "server_vad" },
+                    },
+                })
+                .to_string(),
+            )),
+            Self::Deepgram | Self::AssemblyAi => None,
+        }
+    }
+
+    fn audio_message(self, pcm: Vec<u8>) -> Message {
+        match self {
+            Self::OpenAi => Message::Text(
+                json!({ "type": "input_audio_buffer.append", "audio": B64.encode(pcm) })
+                    .to_string(),
+            ),
+            Self::Deepgram | Self::AssemblyAi => Message::Binary(pcm),
+        }
+    }
+
+    // Asks the service to finalize what it has and end the session
+    fn close_message(self) -> Message {
+        let message = match self {
+            Self::Deepgram => json!({ "type": "CloseStream" }),
+            Self::AssemblyAi => json!({ "type": "Terminate" }),
+            Self::OpenAi => json!({ "type": "input_audio_buffer.commit" }),
+        };
+        Message::Text(message.to_string())
+    }
+
+    // `pending` accumulates OpenAI deltas per item, which arrive as fragments
+    fn parse(
+        self,
+        text: &str,
+        pending: &mut HashMap<String, String>,
+    ) -> Result<Option<Transcript>, String> {
+        let Ok(message) = serde_json::from_str::<Value>(text) else {
+            return Ok(None);
+        };
+        let field = |name: &str| {
+            message
+                .get(name)
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+        };
+        let transcript = |text: &str, is_final: bool| {
+            (!text.trim().is_empty()).then(|| Transcript {
+                text: text.trim().to_string(),
+                is_final,
+            })
+        };
+
+        Ok(match (self, field("type")) {
+            (Self::Deepgram, "Results") => {
+                let text = message
+                    .pointer("/channel/alternatives/0/transcript")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default();
+                let is_final = message.get("is_final").and_then(Value::as_bool) == Some(true);
+                transcript(text, is_final)
+            }
+            (Self::AssemblyAi, "Turn") => {
+                let end_of_turn = message.get("end_of_turn").and_then(Value::as_bool) == Some(true);
+                transcript(field("transcript"), end_of_turn)
+            }
+            (Self::OpenAi, "conversation.item.input_audio_transcription.delta") => {
+                let text = pending.entry(field("item_id").to_string()).or_default();
+                text.push_str(field("delta"));
+                transcript(text, false)
+            }
+            (Self::OpenAi, "conversation.item.input_audio_transcription.completed") => {
+                pending.remove(field("item_id"));
+                transcript(field("transcript"), true)
+            }
+            (_, "error" | "Error") => {
+                let error = message
+                    .pointer("/error/message")
+                    .or_else(|| message.get("error"))
+                    .or_else(|| message.get("description"))
+                    .and_then(Value::as_str)
+                    .unwrap_or(text);
+                return Err(format!("Transcription service error: {}", error));
+            }
+            _ => None,
+        })
+    }
+}
+
+enum Incoming {
+    Transcript(Transcript),
+    Ignored,
+    Closed,
+}
+
+// Streams `frames` until they end or `stop` fires, then waits briefly for trailing finals.
+// Independent of Tauri so it can run against a local mock server.
+pub async fn stream_transcription(
+    config: &RealtimeSttConfig,
+    frames: impl Stream<Item = AudioFrame> + Unpin,
+    sample_rate: u32,
+    stop: oneshot::Receiver<()>,
+    mut on_transcript: impl FnMut(Transcript),
+) -> Result<(), String> {
+    let provider = config.provider;
+    let (mut frames, mut stop) = (frames, stop);
+
+    let (socket, _) = tokio_tungstenite::connect_async(connect_request(config)?)
+        .await
+        .map_err(|e| format!("Failed to connect to transcription service: {}", e))?;
+    let (mut sink, mut stream) = socket.split();
+
+    if let Some(message) = provider.session_message(config.model.as_deref()) {
+        sink.send(message).await.map_err(send_error)?;
+    }
+
+    let mut resampler = Resampler::new(sample_rate, provider.sample_rate());
+    let batch_bytes = (provider.sample_rate() * SEND_INTERVAL_MS / 1000) as usize * 2;
+    let mut pcm = Vec::with_capacity(batch_bytes);
+    let mut pending = HashMap::new();
+
+    loop {
+        tokio::select! {
+            frame = frames.next() => {
+                let Some(frame) = frame else { break };
+                encode_pcm16(&resampler.process(&frame.samples), &mut pcm);
+                if pcm.len() >= batch_bytes {
+                    let message = provider.audio_message(std::mem::take(&mut pcm));
+                    sink.send(message).await.map_err(send_error)?;
+                }
+            }
+            message = stream.next() => match incoming(provider, message, &mut pending)? {
+                Incoming::Transcript(transcript) => on_transcript(transcript),
+                Incoming::Ignored => {}
+                Incoming::Closed => return Ok(()),
+            },
+            _ = &mut stop => break,
+        }
+    }
+
+    // Send the tail, ask the service to finish and collect the remaining finals
+    encode_pcm16(&resampler.flush(), &mut pcm);
+    if !pcm.is_empty() {
+        sink.send(provider.audio_message(pcm))
+            .await
+            .map_err(send_error)?;
+    }
+    sink.send(provider.close_message())
+        .await
+        .map_err(send_error)?;
+
+    let drain = async {
+        loop {
+            match incoming(provider, stream.next().await, &mut pending) {
+                Ok(Incoming::Transcript(transcript)) => on_transcript(transcript),
+                Ok(Incoming::Ignored) => {}
+                Ok(Incoming::Closed) => break,
+                Err(e) => {
+                    warn!("Transcription stream ended with error: {}", e);
+                    break;
+                }
+            }
+        }
+    };
+    let _ = tokio::time::timeout(CLOSE_TIMEOUT, drain).await;
+    let _ = sink.close().await;
+
+    Ok(())
+}
+
+fn connect_request(
+    config: &RealtimeSttConfig,
+) -> Result<tungstenite::handshake::client::Request, String> {
+    let provider = config.provider;
+    let mut url = Url::parse(
+        config
+            .url
+            .as_deref()
+            .unwrap_or_else(|| provider.default_url()),
+    )
+    .map_err(|e| format!("Invalid transcription URL: {}", e))?;
+
+    // Keep parameters the caller already put in the URL
+    let existing: Vec<String> = url.query_pairs().map(|(key, _)| key.into_owned()).collect();
+    for (key, value) in provider.query(config.model.as_deref()) {
+        if !existing.iter().any(|k| k == key) {
+            url.query_pairs_mut().append_pair(key, &value);
+        }
+    }
+
+    let mut request = url
+        .as_str()
+        .into_client_request()
+        .map_err(|e| format!("Invalid transcription URL: {}", e))?;
+
+    let headers = provider
+        .auth_headers(&config.api_key)
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .chain(config.headers.clone());
+    for (key, value) in headers {
+        let name = HeaderName::from_bytes(key.as_bytes())
+            .map_err(|e| format!("Invalid header {}: {}", key, e))?;
+        let value =
+            HeaderValue::from_str(&value).map_err(|e| format!("Invalid header {}: {}", key, e))?;
+        request.headers_mut().insert(name, value);
+    }
+
+    Ok(request)
+}
+
+fn incoming(
+    provider: RealtimeProvider,
+    message: Option<Result<Message, tungstenite::Error>>,
+    pending: &mut HashMap<String, String>,
+) -> Result<Incoming, String> {
+    match message {
+        None => Ok(Incoming::Closed),
+        Some(Err(e)) => Err(format!("Transcription connection failed: {}", e)),
+        Some(Ok(Message::Text(text))) => Ok(provider
+            .parse(&text, pending)?
+            .map_or(Incoming::Ignored, Incoming::Transcript)),
+        Some(Ok(Message::Close(Some(frame)))) if frame.code != CloseCode::Normal => Err(format!(
+            "Transcription service closed the connection ({}): {}",
+            u16::from(frame.code),
+            frame.reason
+        )),
+        Some(Ok(Message::Close(_))) => Ok(Incoming::Closed),
+        // Pings are answered by tungstenite
+        Some(Ok(_)) => Ok(Incoming::Ignored),
+    }
+}
+
+fn send_error(e: tungstenite::Error) -> String {
+    format!("Failed to send audio to transcription service: {}", e)
+}
+
+fn encode_pcm16(samples: &[f32], out: &mut Vec<u8>) {
+    for &s in samples {
+        let sample = (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+        out.extend_from_slice(&sample.to_le_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+    use tokio::task::JoinHandle;
+    use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+
+    const SAMPLE: f32 = 0.25;
+
+    struct Session {
+        uri: String,
+        authorization: Option<String>,
+        messages: Vec<Message>, // Everything the client sent, up to and including its close message
+    }
+
+    // Accepts one client, answers its close message with `replies` and ends the connection
+    #[allow(clippy::result_large_err)] // The handshake callback's error type is tungstenite's
+    async fn mock_service(
+        provider: RealtimeProvider,
+        replies: Vec<Value>,
+    ) -> (String, JoinHandle<Session>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}/stt", listener.local_addr().unwrap());
+
+        let server = tokio::spawn(async move {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut handshake = None;
+            let mut socket = tokio_tungstenite::accept_hdr_async(
+                tcp,
+                |request: &Request, response: Response| {
+                    let authorization = request
+                        .headers()
+                        .get("Authorization")
+                        .and_then(|value| value.to_str().ok())
+                        .map(str::to_string);
+                    handshake = Some((request.uri().to_string(), authorization));
+                    Ok(response)
+                },
+            )
+            .await
+            .unwrap();
+            let (uri, authorization) = handshake.unwrap();
+
+            let close = provider.close_message();
+            let mut messages = Vec::new();
+            while let Some(Ok(message)) = socket.next().await {
+                let done = message == close;
+                messages.push(message);
+                if done {
+                    break;
+                }
+            }
+
+            for reply in replies {
+                socket.send(Message::Text(reply.to_string())).await.unwrap();
+            }
+            let _ = socket.close(None).await;
+            while socket.next().await.is_some() {}
+
+            Session {
+                uri,
+                authorization,
+                messages,
+            }
+        });
+
+        (url, server)
+    }
+
+    fn config(provider: RealtimeProvider, url: String) -> RealtimeSttConfig {
+        RealtimeSttConfig {
+            provider,
+            api_key: "test-key".to_string(),
+            url: Some(url),
+            model: Some("test-model".to_string()),
+            headers: HashMap::new(),
+        }
+    }
+
+    // Frames already at the provider's rate, so the audio is sent unchanged
+    fn frames(count: usize, frame_size: usize) -> impl Stream<Item = AudioFrame> + Unpin {
+        futures_util::stream::iter((0..count).map(move |i| AudioFrame {
+            samples: vec![SAMPLE; frame_size],
+            captured_at_ms: i as u64 * 20,
+        }))
+    }
+
+    fn assert_pcm(pcm: &[u8]) {
+        let expected = ((SAMPLE * i16::MAX as f32) as i16).to_le_bytes();
+        assert!(pcm.chunks(2).all(|sample| sample == expected));
+    }
+
+    async fn run(
+        provider: RealtimeProvider,
+        sample_rate: u32,
+        replies: Vec<Value>,
+    ) -> (Session, Vec<(String, bool)>) {
+        let (url, server) = mock_service(provider, replies).await;
+        let (_stop_tx, stop) = oneshot::channel();
+        let mut transcripts = Vec::new();
+
+        stream_transcription(
+            &config(provider, url),
+            frames(10, 320),
+            sample_rate,
+            stop,
+            |transcript| transcripts.push((transcript.text, transcript.is_final)),
+        )
+        .await
+        .unwrap();
+
+        (server.await.unwrap(), transcripts)
+    }
+
+    #[tokio::test]
+    async fn deepgram_streams_binary_pcm_and_reports_transcripts() {
+        let result = |text: &str, is_final: bool| {
+            json!({
+                "type": "Results",
+                "is_final": is_final,
+                "channel": { "alternatives": [{ "transcript": text }] },
+            })
+        };
+        let replies = vec![
+            result("hello", false),
+            json!({ "type": "Metadata" }),
+            result("hello world", true),
+        ];
+
+        let (session, transcripts) = run(RealtimeProvider::Deepgram, 16_000, replies).await;
+
+        for param in [
+            "encoding=linear16",
+            "sample_rate=16000",
+            "channels=1",
+            "model=test-model",
+        ] {
+            assert!(session.uri.contains(param), "{} missing", session.uri);
+        }
+        assert_eq!(session.authorization.as_deref(), Some("Token test-key"));
+
+        // 3200 samples in 100 ms batches of 1600, then the close request
+        let (close, audio) = session.messages.split_last().unwrap();
+        assert_eq!(*close, RealtimeProvider::Deepgram.close_message());
+        assert_eq!(audio.len(), 2);
+        for message in audio {
+            let Message::Binary(pcm) = message else {
+                panic!("expected binary audio, got {:?}", message);
+            };
+            assert_eq!(pcm.len(), 3200);
+            assert_pcm(pcm);
+        }
+
+        assert_eq!(
+            transcripts,
+            [
+                ("hello".to_string(), false),
+                ("hello world".to_string(), true)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn openai_sends_session_update_and_joins_deltas() {
+        let item = |kind: &str, field: &str, text: &str| {
+            json!({
+                "type": format!("conversation.item.input_audio_transcription.{}", kind),
+                "item_id": "item-1",
+                field: text,
+            })
+        };
+        let replies = vec![
+            item("delta", "delta", "Hel"),
+            item("delta", "delta", "lo"),
+            item("completed", "transcript", "Hello."),
+        ];
+
+        let (session, transcripts) = run(RealtimeProvider::OpenAi, 24_000, replies).await;
+
+        assert!(session.uri.contains("intent=transcription"));
+        assert!(!session.uri.contains("model="));
+        assert_eq!(session.authorization.as_deref(), Some("Bearer test-key"));
+
+        let text = |message: &Message| match message {
+            Message::Text(text) => serde_json::from_str::<Value>(text).unwrap(),
+            other => panic!("expected a JSON message, got {:?}", other),
+        };
+        let (update, rest) = session.messages.split_first().unwrap();
+        let (close, audio) = rest.split_last().unwrap();
+
+        let update = text(update);
+        assert_eq!(update["type"], "transcription_session.update");
+        assert_eq!(
+            update["session"]["input_audio_transcription"]["model"],
+            "test-model"
+        );
+        assert_eq!(*close, RealtimeProvider::OpenAi.close_message());
+
+        // A batch goes out once 100 ms (2400 samples) has built up: 8 frames, then a 2-frame tail
+        let pcm: Vec<Vec<u8>> = audio
+            .iter()
+            .map(|message| {
+                let append = text(message);
+                assert_eq!(append["type"], "input_audio_buffer.append");
+                B64.decode(append["audio"].as_str().unwrap()).unwrap()
+            })
+            .collect();
+        assert_eq!(pcm.iter().map(Vec::len).collect::<Vec<_>>(), [5120, 1280]);
+        pcm.iter().for_each(|pcm| assert_pcm(pcm));
+
+        assert_eq!(
+            transcripts,
+            [
+                ("Hel".to_string(), false),
+                ("Hello".to_string(), false),
+                ("Hello.".to_string(), true)
+            ]
+        );
+    }
+}
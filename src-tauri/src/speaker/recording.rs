@@ -0,0 +1,302 @@
+// Meetwings meeting recorder: full capture streams written incrementally to disk,
+// one track per source, with a metadata file linking them to a conversation
+use hound::{SampleFormat, WavSpec, WavWriter};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use super::resample::resample;
+use super::AudioSource;
+
+const FINALIZE_INTERVAL: Duration = Duration::from_secs(2); // Bounds what a crash can lose
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingTrack {
+    pub source: AudioSource,
+    pub path: String,
+    pub sample_rate: u32,
+}
+
+// Contents of `<id>.json` next to the tracks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeetingRecordingInfo {
+    pub id: String,
+    pub conversation_id: String,
+    pub started_at_ms: u64,
+    pub ended_at_ms: Option<u64>, // None while recording, or if the app exited mid-meeting
+    pub tracks: Vec<RecordingTrack>,
+}
+
+enum Track {
+    Writing {
+        writer: WavWriter<BufWriter<File>>,
+        sample_rate: u32,
+        last_finalized: Instant,
+    },
+    Failed,
+}
+
+pub struct MeetingRecording {
+    dir: PathBuf,
+    info: Mutex<MeetingRecordingInfo>,
+    tracks: Mutex<Option<HashMap<AudioSource, Track>>>, // None once finished
+}
+
+impl MeetingRecording {
+    pub fn start(dir: &Path, conversation_id: String) -> Result<Self, String> {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create recordings directory: {}", e))?;
+
+        let recording = Self {
+            dir: dir.to_path_buf(),
+            info: Mutex::new(MeetingRecordingInfo {
+                id: uuid::Uuid::new_v4().to_string(),
+                conversation_id,
+                started_at_ms: unix_millis(),
+                ended_at_ms: None,
+                tracks: Vec::new(),
+            }),
+            tracks: Mutex::new(Some(HashMap::new())),
+        };
+        recording.save_info(&recording.info())?;
+        Ok(recording)
+    }
+
+    pub fn info(&self) -> MeetingRecordingInfo {
+        self.info.lock().unwrap().clone()
+    }
+
+    // Appends captured samples to the source's track, opening it on first use.
+    // Returns an error only the first time a track fails; it is skipped afterwards.
+    pub fn write(
+        &self,
+        source: AudioSource,
+        sample_rate: u32,
+        samples: &[f32],
+    ) -> Result<(), String> {
+        let mut tracks = self.tracks.lock().unwrap();
+        let Some(tracks) = tracks.as_mut() else {
+            return Ok(());
+        };
+
+        if !tracks.contains_key(&source) {
+            match self.open_track(source, sample_rate) {
+                Ok(track) => {
+                    tracks.insert(source, track);
+                }
+                Err(e) => {
+                    tracks.insert(source, Track::Failed);
+                    return Err(format!(
+                        "Failed to create {:?} recording track: {}",
+                        source, e
+                    ));
+                }
+            }
+        }
+
+        let Some(track) = tracks.get_mut(&source) else {
+            return Ok(());
+        };
+        let Track::Writing {
+            writer,
+            sample_rate: track_rate,
+            last_finalized,
+        } = track
+        else {
+            return Ok(());
+        };
+
+        // A capture restarted on another device keeps the track's rate
+        let resampled;
+        let samples = if *track_rate == sample_rate {
+            samples
+        } else {
+            resampled = resample(samples, sample_rate, *track_rate);
+            &resampled
+        };
+
+        let mut result = samples
+            .iter()
+            .try_for_each(|&s| writer.write_sample((s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16));
+
+        // Rewrites the header so the file stays playable if the app dies
+        if result.is_ok() && last_finalized.elapsed() >= FINALIZE_INTERVAL {
+            result = writer.flush();
+            *last_finalized = Instant::now();
+        }
+
+        result.map_err(|e| {
+            *track = Track::Failed;
+            format!("Failed to write {:?} recording track: {}", source, e)
+        })
+    }
+
+    pub fn finish(&self) -> Result<MeetingRecordingInfo, String> {
+        let tracks = self.tracks.lock().unwrap().take().unwrap_or_default();
+
+        let mut errors = Vec::new();
+        for (source, track) in tracks {
+            if let Track::Writing { writer, .. } = track {
+                if let Err(e) = writer.finalize() {
+                    errors.push(format!("{:?}: {}", source, e));
+                }
+            }
+        }
+
+        let info = {
+            let mut info = self.info.lock().unwrap();
+            info.ended_at_ms = Some(unix_millis());
+            info.clone()
+        };
+        self.save_info(&info)?;
+
+        if errors.is_empty() {
+            Ok(info)
+        } else {
+            Err(format!(
+                "Failed to finalize recording: {}",
+                errors.join(", ")
+            ))
+        }
+    }
+
+    fn open_track(&self, source: AudioSource, sample_rate: u32) -> Result<Track, String> {
+        let info = {
+            let mut info = self.info.lock().unwrap();
+            let path = self
+                .dir
+                .join(format!("{}-{}.wav", info.id, source_name(source)));
+            info.tracks.push(RecordingTrack {
+                source,
+                path: path.to_string_lossy().into_owned(),
+                sample_rate,
+            });
+            info.clone()
+        };
+
+        let spec = WavSpec {
+            channels: 1,
+            sample_rate,
+            bits_per_sample: 16,
+            sample_format: SampleFormat::Int,
+        };
+        let path = &info.tracks.last().unwrap().path;
+        let writer = WavWriter::create(path, spec).map_err(|e| e.to_string())?;
+
+        self.save_info(&info)?;
+        Ok(Track::Writing {
+            writer,
+            sample_rate,
+            last_finalized: Instant::now(),
+        })
+    }
+
+    fn save_info(&self, info: &MeetingRecordingInfo) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(info)
+            .map_err(|e| format!("Failed to serialize recording info: {}", e))?;
+        fs::write(self.dir.join(format!("{}.json", info.id)), content)
+            .map_err(|e| format!("Failed to write recording info: {}", e))
+    }
+}
+
+// Recordings in `dir`, newest first
+pub fn list_recordings(dir: &Path) -> Vec<MeetingRecordingInfo> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut recordings: Vec<MeetingRecordingInfo> = entries
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+        .filter_map(|content| serde_json::from_str(&content).ok())
+        .collect();
+    recordings.sort_by(|a, b| b.started_at_ms.cmp(&a.started_at_ms));
+    recordings
+}
+
+pub fn delete_recording(dir: &Path, id: &str) -> Result<(), String> {
+    // IDs are UUIDs; anything else could escape the directory
+    uuid::Uuid::parse_str(id).map_err(|_| format!("Invalid recording ID: {}", id))?;
+
+    let info_path = dir.join(format!("{}.json", id));
+    let content = fs::read_to_string(&info_path)
+        .map_err(|e| format!("Failed to read recording {}: {}", id, e))?;
+    let info: MeetingRecordingInfo = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse recording {}: {}", id, e))?;
+
+    for track in &info.tracks {
+        let _ = fs::remove_file(&track.path);
+    }
+    fs::remove_file(&info_path).map_err(|e| format!("Failed to delete recording {}: {}", id, e))
+}
+
+fn source_name(source: AudioSource) -> &'static str {
+    match source {
+        AudioSource::Mic => "mic",
+        AudioSource::System => "system",
+    }
+}
+
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::speaker::SpeakerInput;
+    use futures_util::StreamExt;
+
+    #[tokio::test]
+    async fn generator_capture_round_trips_through_a_track() {
+        let dir = std::env::temp_dir().join(format!("meetwings-rec-{}", uuid::Uuid::new_v4()));
+        let recording = MeetingRecording::start(&dir, "conversation".to_string()).unwrap();
+
+        // 1.5 s of tone at the generator's 16 kHz, written frame by frame like a capture
+        let stream =
+            SpeakerInput::new_with_device(Some("generator://tone:440:1500?realtime=false".into()))
+                .unwrap()
+                .stream();
+        let sample_rate = stream.sample_rate();
+        let mut frames = stream.frames(1024);
+        while let Some(frame) = frames.next().await {
+            recording
+                .write(AudioSource::System, sample_rate, &frame.samples)
+                .unwrap();
+        }
+        let info = recording.finish().unwrap();
+
+        assert!(info.ended_at_ms.is_some());
+        assert_eq!(info.tracks.len(), 1);
+        let track = &info.tracks[0];
+        assert_eq!(
+            (track.source, track.sample_rate),
+            (AudioSource::System, 16_000)
+        );
+
+        let reader = hound::WavReader::open(&track.path).unwrap();
+        let spec = reader.spec();
+        assert_eq!((spec.channels, spec.sample_rate), (1, 16_000));
+        assert_eq!(
+            (spec.bits_per_sample, spec.sample_format),
+            (16, SampleFormat::Int)
+        );
+        assert_eq!(reader.duration(), 24_000);
+
+        // The metadata file lists it, and deleting removes the track too
+        let listed = list_recordings(&dir);
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, info.id);
+        delete_recording(&dir, &info.id).unwrap();
+        assert!(!Path::new(&track.path).exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
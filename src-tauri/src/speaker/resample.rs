@@ -0,0 +1,227 @@
+// Meetwings band-limited resampler (windowed sinc, arbitrary ratio)
+use std::f64::consts::PI;
+
+const ZERO_CROSSINGS: usize = 16; // Kernel half-width at the cutoff frequency
+const TABLE_RESOLUTION: usize = 256; // Kernel samples per input sample
+const ROLLOFF: f64 = 0.95; // Cutoff relative to the lower of the two Nyquist rates
+
+// Streaming resampler: feed blocks with `process`, then `flush` at the end.
+pub struct Resampler {
+    input_rate: u32,
+    output_rate: u32,
+    step: f64,         // Input samples advanced per output sample
+    half_width: usize, // Taps on each side of the read position
+    kernel: Vec<f32>,  // One side of the kernel, indexed by distance * TABLE_RESOLUTION
+    buffer: Vec<f32>,  // Input not yet consumed (plus history for the left taps)
+    position: f64,     // Read position into `buffer`
+}
+
+impl Resampler {
+    pub fn new(input_rate: u32, output_rate: u32) -> Self {
+        let input_rate = input_rate.max(1);
+        let output_rate = output_rate.max(1);
+        let step = input_rate as f64 / output_rate as f64;
+
+        // Downsampling lowers the cutoff, which widens the kernel in input samples
+        let cutoff = ROLLOFF * (output_rate as f64 / input_rate as f64).min(1.0);
+        let half_width = (ZERO_CROSSINGS as f64 / cutoff).ceil() as usize;
+
+        let kernel = (0..half_width * TABLE_RESOLUTION + 2)
+            .map(|i| {
+                let x = i as f64 / TABLE_RESOLUTION as f64;
+                if x >= half_width as f64 {
+                    return 0.0;
+                }
+                let arg = PI * cutoff * x;
+                let sinc = if arg == 0.0 { 1.0 } else { arg.sin() / arg };
+                (cutoff * sinc * blackman(x / half_width as f64)) as f32
+            })
+            .collect();
+
+        Self {
+            input_rate,
+            output_rate,
+            step,
+            half_width,
+            kernel,
+            // Leading silence lets the first output sample line up with the first input sample
+            buffer: vec![0.0; half_width],
+            position: half_width as f64,
+        }
+    }
+
+    pub fn is_passthrough(&self) -> bool {
+        self.input_rate == self.output_rate
+    }
+
+    // Resamples the next block. Output lags input by `half_width` samples until `flush`.
+    pub fn process(&mut self, input: &[f32]) -> Vec<f32> {
+        if self.is_passthrough() {
+            return input.to_vec();
+        }
+
+        self.buffer.extend_from_slice(input);
+
+        let mut output = Vec::with_capacity((input.len() as f64 / self.step).ceil() as usize + 1);
+        while (self.position as usize) + self.half_width < self.buffer.len() {
+            output.push(self.interpolate(self.position));
+            self.position += self.step;
+        }
+
+        // Drop input no future output sample can reach
+        let keep_from = ((self.position as usize) + 1)
+            .saturating_sub(self.half_width)
+            .min(self.buffer.len());
+        self.buffer.drain(..keep_from);
+        self.position -= keep_from as f64;
+
+        output
+    }
+
+    // Drains the samples still held back by the kernel's look-ahead
+    pub fn flush(&mut self) -> Vec<f32> {
+        if self.is_passthrough() {
+            return Vec::new();
+        }
+        let tail = vec![0.0; self.half_width];
+        self.process(&tail)
+    }
+
+    fn interpolate(&self, t: f64) -> f32 {
+        let center = t as usize;
+        let first = center + 1 - self.half_width;
+        let last = center + self.half_width;
+
+        let mut acc = 0.0f32;
+        for (k, &sample) in self.buffer[first..=last].iter().enumerate() {
+            let distance = ((first + k) as f64 - t).abs();
+            acc += sample * self.kernel_at(distance);
+        }
+        acc
+    }
+
+    fn kernel_at(&self, distance: f64) -> f32 {
+        let index = distance * TABLE_RESOLUTION as f64;
+        let i = index as usize;
+        if i + 1 >= self.kernel.len() {
+            return 0.0;
+        }
+        let frac = (index - i as f64) as f32;
+        self.kernel[i] + (self.kernel[i + 1] - self.kernel[i]) * frac
+    }
+}
+
+// One-shot resample of a complete buffer
+pub fn resample(samples: &[f32], input_rate: u32, output_rate: u32) -> Vec<f32> {
+    if input_rate == output_rate || samples.is_empty() {
+        return samples.to_vec();
+    }
+
+    let mut resampler = Resampler::new(input_rate, output_rate);
+    let mut output = resampler.process(samples);
+    output.extend(resampler.flush());
+
+    let expected = (samples.len() as u64 * output_rate as u64 / input_rate as u64) as usize;
+    output.truncate(expected);
+    output
+}
+
+// Blackman window over u in [0, 1] (one side, peak at 0)
+fn blackman(u: f64) -> f64 {
+    0.42 + 0.5 * (PI * u).cos() + 0.08 * (2.0 * PI * u).cos()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::TAU;
+
+    // Ignores the kernel's ramp-in / ramp-out at either end
+    const EDGE: usize = 200;
+
+    fn tone(hz: f32, sample_rate: u32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| 0.5 * (TAU * hz * i as f32 / sample_rate as f32).sin())
+            .collect()
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn output_length_follows_rate_ratio() {
+        assert_eq!(resample(&vec![0.0; 48_000], 48_000, 16_000).len(), 16_000);
+        assert_eq!(resample(&vec![0.0; 44_100], 44_100, 16_000).len(), 16_000);
+        assert_eq!(resample(&vec![0.0; 1_001], 48_000, 16_000).len(), 333);
+        assert_eq!(resample(&vec![0.0; 1_000], 44_100, 16_000).len(), 362);
+    }
+
+    #[test]
+    fn passband_tone_is_preserved() {
+        for input_rate in [48_000, 44_100] {
+            let output = resample(
+                &tone(1000.0, input_rate, input_rate as usize),
+                input_rate,
+                16_000,
+            );
+            let expected = tone(1000.0, 16_000, output.len());
+
+            let interior = EDGE..output.len() - EDGE;
+            let max_error = output[interior.clone()]
+                .iter()
+                .zip(&expected[interior])
+                .map(|(a, b)| (a - b).abs())
+                .fold(0.0, f32::max);
+            assert!(
+                max_error < 0.005,
+                "{} Hz: max error {}",
+                input_rate,
+                max_error
+            );
+        }
+    }
+
+    #[test]
+    fn tone_above_new_nyquist_is_removed() {
+        for (input_rate, hz) in [(48_000, 9_000.0), (48_000, 12_000.0), (44_100, 10_000.0)] {
+            let input = tone(hz, input_rate, input_rate as usize);
+            let output = resample(&input, input_rate, 16_000);
+
+            let attenuation_db =
+                20.0 * (rms(&output[EDGE..output.len() - EDGE]) / rms(&input)).log10();
+            assert!(
+                attenuation_db < -50.0,
+                "{} Hz from {} Hz: {:.1} dB",
+                hz,
+                input_rate,
+                attenuation_db
+            );
+        }
+    }
+
+    #[test]
+    fn streaming_matches_one_shot() {
+        let input = tone(440.0, 44_100, 20_000);
+        let one_shot = resample(&input, 44_100, 16_000);
+
+        let mut resampler = Resampler::new(44_100, 16_000);
+        let mut streamed = Vec::new();
+        let mut rest = input.as_slice();
+        for size in [1, 7, 160, 1023, 3, 4096].iter().cycle() {
+            if rest.is_empty() {
+                break;
+            }
+            let (block, tail) = rest.split_at((*size).min(rest.len()));
+            streamed.extend(resampler.process(block));
+            rest = tail;
+        }
+        streamed.extend(resampler.flush());
+        streamed.truncate(one_shot.len());
+
+        assert_eq!(streamed.len(), one_shot.len());
+        for (i, (a, b)) in streamed.iter().zip(&one_shot).enumerate() {
+            assert!((a - b).abs() < 1e-5, "sample {}: {} vs {}", i, a, b);
+        }
+    }
+}
@@ -0,0 +1,65 @@
+// Meetwings segment store: encoded speech segments kept on the Rust side so events
+// only carry IDs, and the audio crosses IPC (as raw bytes) only when it is needed
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use super::encode::AudioEncoding;
+
+const MAX_SEGMENTS: usize = 256;
+const MAX_BYTES: usize = 64 * 1024 * 1024; // Oldest segments are evicted past either limit
+
+// What segment events carry instead of the audio
+#[derive(Debug, Clone, Serialize)]
+pub struct SegmentInfo {
+    pub segment_id: u64,
+    pub encoding: AudioEncoding,
+    pub duration_ms: u64,
+    pub size_bytes: usize,
+}
+
+#[derive(Default)]
+pub struct SegmentStore {
+    next_id: AtomicU64,
+    segments: Mutex<VecDeque<(u64, Arc<[u8]>)>>, // Oldest first
+}
+
+impl SegmentStore {
+    pub fn insert(&self, encoding: AudioEncoding, data: Vec<u8>, duration_ms: u64) -> SegmentInfo {
+        let segment_id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let info = SegmentInfo {
+            segment_id,
+            encoding,
+            duration_ms,
+            size_bytes: data.len(),
+        };
+
+        let mut segments = self.segments.lock().unwrap();
+        segments.push_back((segment_id, data.into()));
+
+        let mut total: usize = segments.iter().map(|(_, data)| data.len()).sum();
+        while segments.len() > 1 && (segments.len() > MAX_SEGMENTS || total > MAX_BYTES) {
+            if let Some((_, evicted)) = segments.pop_front() {
+                total -= evicted.len();
+            }
+        }
+
+        info
+    }
+
+    pub fn get(&self, segment_id: u64) -> Option<Arc<[u8]>> {
+        let segments = self.segments.lock().unwrap();
+        segments
+            .iter()
+            .find(|(id, _)| *id == segment_id)
+            .map(|(_, data)| data.clone())
+    }
+
+    pub fn remove(&self, segment_id: u64) {
+        self.segments
+            .lock()
+            .unwrap()
+            .retain(|(id, _)| *id != segment_id);
+    }
+}
@@ -0,0 +1,128 @@
+// Meetwings spectral helpers: radix-2 FFT and windowed power spectra
+use std::f32::consts::PI;
+
+// In-place complex FFT for one power-of-two size
+pub struct Fft {
+    size: usize,
+    twiddles: Vec<(f32, f32)>, // (cos, -sin) of 2πk/size for k < size/2
+    bit_reversed: Vec<usize>,
+}
+
+impl Fft {
+    pub fn new(size: usize) -> Self {
+        let size = size.next_power_of_two().max(2);
+        let bits = size.trailing_zeros();
+
+        let twiddles = (0..size / 2)
+            .map(|k| {
+                let angle = 2.0 * PI * k as f32 / size as f32;
+                (angle.cos(), -angle.sin())
+            })
+            .collect();
+        let bit_reversed = (0..size)
+            .map(|i| i.reverse_bits() >> (usize::BITS - bits))
+            .collect();
+
+        Self {
+            size,
+            twiddles,
+            bit_reversed,
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    // Forward transform; both slices must be `size` long
+    pub fn forward(&self, re: &mut [f32], im: &mut [f32]) {
+        for i in 0..self.size {
+            let j = self.bit_reversed[i];
+            if j > i {
+                re.swap(i, j);
+                im.swap(i, j);
+            }
+        }
+
+        let mut len = 2;
+        while len <= self.size {
+            let half = len / 2;
+            let stride = self.size / len;
+            for start in (0..self.size).step_by(len) {
+                for k in 0..half {
+                    let (wr, wi) = self.twiddles[k * stride];
+                    let (a, b) = (start + k, start + k + half);
+                    let tr = re[b] * wr - im[b] * wi;
+                    let ti = re[b] * wi + im[b] * wr;
+                    re[b] = re[a] - tr;
+                    im[b] = im[a] - ti;
+                    re[a] += tr;
+                    im[a] += ti;
+                }
+            }
+            len *= 2;
+        }
+    }
+
+    // Inverse transform, scaled so inverse(forward(x)) == x
+    pub fn inverse(&self, re: &mut [f32], im: &mut [f32]) {
+        im.iter_mut().for_each(|v| *v = -*v);
+        self.forward(re, im);
+
+        let scale = 1.0 / self.size as f32;
+        re.iter_mut().for_each(|v| *v *= scale);
+        im.iter_mut().for_each(|v| *v = -*v * scale);
+    }
+}
+
+// Periodic Hann window
+pub fn hann(size: usize) -> Vec<f32> {
+    (0..size)
+        .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / size as f32).cos())
+        .collect()
+}
+
+// Hann-windowed power spectrum of real blocks, zero-padded to a power of two
+pub struct PowerSpectrum {
+    fft: Fft,
+    window: Vec<f32>,
+    re: Vec<f32>,
+    im: Vec<f32>,
+    power: Vec<f32>,
+}
+
+impl PowerSpectrum {
+    pub fn new(block_size: usize) -> Self {
+        let fft = Fft::new(block_size);
+        let size = fft.size();
+
+        Self {
+            window: hann(block_size.max(1)),
+            re: vec![0.0; size],
+            im: vec![0.0; size],
+            power: vec![0.0; size / 2 + 1],
+            fft,
+        }
+    }
+
+    // Width of one bin for the given sample rate
+    pub fn bin_hz(&self, sample_rate: u32) -> f32 {
+        sample_rate as f32 / self.fft.size() as f32
+    }
+
+    // Bins 0..=size/2; blocks longer than the window are truncated
+    pub fn compute(&mut self, block: &[f32]) -> &[f32] {
+        self.re.fill(0.0);
+        self.im.fill(0.0);
+        for ((re, &s), &w) in self.re.iter_mut().zip(block).zip(&self.window) {
+            *re = s * w;
+        }
+
+        self.fft.forward(&mut self.re, &mut self.im);
+
+        for (k, p) in self.power.iter_mut().enumerate() {
+            *p = self.re[k] * self.re[k] + self.im[k] * self.im[k];
+        }
+        &self.power
+    }
+}
@@ -0,0 +1,241 @@
+// Meetwings capture pipeline tests: generator and WAV inputs played through
+// start_system_audio_capture on the mock runtime, checking the VAD event sequence
+use futures_util::StreamExt;
+use hound::{SampleFormat, WavSpec, WavWriter};
+use std::f32::consts::TAU;
+use std::sync::{Arc, Mutex};
+use tauri::test::{mock_builder, mock_context, noop_assets, MockRuntime};
+use tauri::{App, Listener, Manager};
+
+use super::{start_system_audio_capture, ChannelMode, SpeakerInput, VadConfig};
+
+const VAD_EVENTS: [&str; 3] = ["speech-start", "speech-detected", "speech-discarded"];
+
+fn mock_app() -> App<MockRuntime> {
+    mock_builder()
+        .manage(crate::AudioState::default())
+        .build(mock_context(noop_assets()))
+        .expect("Failed to build mock app")
+}
+
+// Captures `device_id` until its audio ends and returns the VAD events in emission order
+async fn vad_events(device_id: &str) -> Vec<&'static str> {
+    let app = mock_app();
+    let events = Arc::new(Mutex::new(Vec::new()));
+    for name in VAD_EVENTS {
+        let events = events.clone();
+        app.listen_any(name, move |_| events.lock().unwrap().push(name));
+    }
+
+    start_system_audio_capture(
+        app.handle().clone(),
+        Some(VadConfig::default()),
+        Some(device_id.to_string()),
+    )
+    .await
+    .expect("Capture failed to start");
+
+    let task = app
+        .state::<crate::AudioState>()
+        .stream_task
+        .lock()
+        .unwrap()
+        .take()
+        .expect("Capture task missing");
+    task.await.expect("Capture task panicked");
+
+    let events = events.lock().unwrap().clone();
+    events
+}
+
+// Default VAD timing at 16 kHz: 64 ms hops, ~0.45 s minimum speech, ~2.9 s of silence to end
+fn generator(pattern: &str) -> String {
+    format!("generator://{}?realtime=false", pattern)
+}
+
+#[tokio::test]
+async fn utterance_is_detected() {
+    let events = vad_events(&generator("silence:500,tone:220:1500,silence:4000")).await;
+    assert_eq!(events, ["speech-start", "speech-detected"]);
+}
+
+#[tokio::test]
+async fn short_blip_is_discarded() {
+    let events = vad_events(&generator("silence:500,tone:440:150,silence:4000")).await;
+    assert_eq!(events, ["speech-start", "speech-discarded"]);
+}
+
+#[tokio::test]
+async fn quiet_noise_is_ignored() {
+    let events = vad_events(&generator("noise:5000:0.002")).await;
+    assert!(events.is_empty(), "unexpected events: {:?}", events);
+}
+
+#[tokio::test]
+async fn pauses_split_utterances() {
+    let events = vad_events(&generator(
+        "tone:220:1000,silence:4000,noise:1000:0.2,silence:4000",
+    ))
+    .await;
+    assert_eq!(
+        events,
+        [
+            "speech-start",
+            "speech-detected",
+            "speech-start",
+            "speech-detected"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn short_gaps_stay_in_one_utterance() {
+    let events = vad_events(&generator(
+        "tone:220:800,silence:600,tone:330:800,silence:4000",
+    ))
+    .await;
+    assert_eq!(events, ["speech-start", "speech-detected"]);
+}
+
+#[tokio::test]
+async fn stereo_wav_file_is_detected() {
+    // 48 kHz stereo with the voice on the left channel only, as a meeting app might play it
+    let path = std::env::temp_dir().join(format!("meetwings-vad-{}.wav", uuid::Uuid::new_v4()));
+    let spec = WavSpec {
+        channels: 2,
+        sample_rate: 48_000,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let mut writer = WavWriter::create(&path, spec).unwrap();
+    for i in 0..48_000 * 6 {
+        let t = i as f32 / 48_000.0;
+        let left = if (0.5..2.0).contains(&t) {
+            0.3 * (TAU * 220.0 * t).sin()
+        } else {
+            0.0
+        };
+        writer
+            .write_sample((left * i16::MAX as f32) as i16)
+            .unwrap();
+        writer.write_sample(0i16).unwrap();
+    }
+    writer.finalize().unwrap();
+
+    let events = vad_events(&format!("file://{}?realtime=false", path.display())).await;
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(events, ["speech-start", "speech-detected"]);
+}
+
+#[tokio::test]
+async fn interleaved_stream_keeps_both_channels() {
+    let path = std::env::temp_dir().join(format!("meetwings-ch-{}.wav", uuid::Uuid::new_v4()));
+    let spec = WavSpec {
+        channels: 2,
+        sample_rate: 16_000,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let mut writer = WavWriter::create(&path, spec).unwrap();
+    for _ in 0..1_600 {
+        writer.write_sample(i16::MAX / 2).unwrap();
+        writer.write_sample(i16::MIN / 4).unwrap();
+    }
+    writer.finalize().unwrap();
+
+    let stream =
+        SpeakerInput::new_with_device(Some(format!("file://{}?realtime=false", path.display())))
+            .unwrap()
+            .with_channel_mode(ChannelMode::Interleaved)
+            .stream();
+    assert_eq!(stream.channels(), 2);
+
+    let samples: Vec<f32> = stream.collect().await;
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(samples.len(), 3_200);
+    for frame in samples.chunks_exact(2) {
+        assert!((frame[0] - 0.5).abs() < 1e-3 && (frame[1] + 0.25).abs() < 1e-3);
+    }
+}
+
+// A stream that ends at once must not leave a stale task behind ("Capture already running")
+#[tokio::test(flavor = "multi_thread")]
+async fn capture_restarts_after_its_stream_ends() {
+    let app = mock_app();
+    for _ in 0..3 {
+        start_system_audio_capture(app.handle().clone(), None, Some(generator("silence:10")))
+            .await
+            .expect("Capture failed to start");
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while app
+            .state::<crate::AudioState>()
+            .stream_task
+            .lock()
+            .unwrap()
+            .is_some()
+        {
+            assert!(
+                std::time::Instant::now() < deadline,
+                "Capture slot never cleared"
+            );
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+    }
+}
+
+#[tokio::test]
+async fn invalid_generator_is_rejected() {
+    let app = mock_app();
+    let result = start_system_audio_capture(
+        app.handle().clone(),
+        None,
+        Some("generator://hum:50".to_string()),
+    )
+    .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn missing_wav_file_is_rejected() {
+    let app = mock_app();
+    let path = std::env::temp_dir().join(format!("meetwings-missing-{}.wav", uuid::Uuid::new_v4()));
+    let result = start_system_audio_capture(
+        app.handle().clone(),
+        None,
+        Some(format!("file://{}", path.display())),
+    )
+    .await;
+    assert!(result.is_err());
+    assert!(app
+        .state::<crate::AudioState>()
+        .stream_task
+        .lock()
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn overlap_as_long_as_a_chunk_is_rejected() {
+    let app = mock_app();
+    let config = VadConfig {
+        enabled: false,
+        chunked_continuous: true,
+        continuous_chunk_secs: 5,
+        continuous_chunk_overlap_ms: 5_000,
+        ..VadConfig::default()
+    };
+    let result = start_system_audio_capture(
+        app.handle().clone(),
+        Some(config),
+        Some(generator("silence:1000")),
+    )
+    .await;
+    assert!(result.is_err());
+    assert!(app
+        .state::<crate::AudioState>()
+        .stream_task
+        .lock()
+        .unwrap()
+        .is_none());
+}
@@ -2,15 +2,30 @@
 use anyhow::Result;
 use futures_util::Stream;
 use std::collections::VecDeque;
-use std::sync::{mpsc, Arc, Mutex};
-use std::task::{Poll, Waker};
+use std::sync::mpsc;
+use std::task::Poll;
 use std::thread;
-use std::time::Duration;
-use tracing::error;
-use wasapi::{get_default_device, Direction, SampleType, StreamMode, WaveFormat};
+use std::time::{Duration, Instant};
+use tracing::{error, warn};
+use wasapi::{
+    get_default_device, AudioCaptureClient, AudioClient, Direction, Handle, SampleType, StreamMode,
+    WasapiError, WaveFormat,
+};
+
+use super::downmix::ChannelMode;
+use super::frames::AudioFrame;
+use super::queue::{sample_queue, QueueHandle, SampleConsumer, SampleProducer, QUEUE_CAPACITY};
+use super::{AudioDevice, AudioDeviceKind, DeviceEvent, DeviceEventCallback};
+
+const DEFAULT_DEVICE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+const REOPEN_ATTEMPTS: u32 = 10;
+const REOPEN_BACKOFF: Duration = Duration::from_millis(500);
+const PACKET_WAIT_MS: u32 = 200; // Loopback sends nothing during silence, so this is no error
 
 pub struct SpeakerInput {
     device_index: Option<usize>,
+    on_device_event: Option<DeviceEventCallback>,
+    channel_mode: ChannelMode,
 }
 
 impl SpeakerInput {
@@ -20,63 +35,117 @@ impl SpeakerInput {
                 .and_then(|s| s.parse::<usize>().ok())
         });
 
-        Ok(Self { device_index })
+        Ok(Self {
+            device_index,
+            on_device_event: None,
+            channel_mode: ChannelMode::default(),
+        })
+    }
+
+    pub fn set_device_event_callback(&mut self, callback: DeviceEventCallback) {
+        self.on_device_event = Some(callback);
+    }
+
+    pub fn set_channel_mode(&mut self, mode: ChannelMode) {
+        self.channel_mode = mode;
+    }
+
+    // Lists render endpoints that can be captured in loopback
+    pub fn list_devices() -> Result<Vec<AudioDevice>> {
+        use wasapi::DeviceCollection;
+
+        let default_id = get_default_device(&Direction::Render)
+            .and_then(|device| device.get_id())
+            .ok();
+
+        let collection = DeviceCollection::new(&Direction::Render)?;
+        let count = collection.get_nbr_devices()?;
+        let mut devices = Vec::with_capacity(count as usize);
+
+        for index in 0..count {
+            let device = match collection.get_device_at_index(index) {
+                Ok(device) => device,
+                Err(e) => {
+                    error!("Failed to open render device {}: {}", index, e);
+                    continue;
+                }
+            };
+
+            let name = device
+                .get_friendlyname()
+                .unwrap_or_else(|_| format!("Output device {}", index));
+            let (channels, sample_rate) = device
+                .get_iaudioclient()
+                .and_then(|client| client.get_mixformat())
+                .map(|format| (format.get_nchannels(), format.get_samplespersec()))
+                .unwrap_or((2, 44100));
+
+            devices.push(AudioDevice {
+                id: format!("windows_output_{}", index),
+                name,
+                kind: AudioDeviceKind::Loopback,
+                is_default: default_id.is_some() && device.get_id().ok() == default_id,
+                channels,
+                sample_rate,
+            });
+        }
+
+        Ok(devices)
     }
 
     // Starts the audio stream
     pub fn stream(self) -> SpeakerStream {
-        let sample_queue = Arc::new(Mutex::new(VecDeque::new()));
-        let waker_state = Arc::new(Mutex::new(WakerState {
-            waker: None,
-            has_data: false,
-            shutdown: false,
-        }));
+        let (producer, consumer) = sample_queue(QUEUE_CAPACITY);
         let (init_tx, init_rx) = mpsc::channel();
 
-        let queue_clone = sample_queue.clone();
-        let waker_clone = waker_state.clone();
         let device_index = self.device_index;
+        let on_device_event = self.on_device_event;
+        let channel_mode = self.channel_mode;
 
         let capture_thread = thread::spawn(move || {
-            if let Err(e) =
-                SpeakerStream::capture_audio_loop(queue_clone, waker_clone, init_tx, device_index)
-            {
+            if let Err(e) = SpeakerStream::capture_audio_loop(
+                producer,
+                init_tx,
+                device_index,
+                on_device_event,
+                channel_mode,
+            ) {
                 error!("Meetwings Audio capture loop failed: {}", e);
             }
         });
 
-        let actual_sample_rate = match init_rx.recv_timeout(Duration::from_secs(5)) {
-            Ok(Ok(rate)) => rate,
+        let (actual_sample_rate, channels) = match init_rx.recv_timeout(Duration::from_secs(5)) {
+            Ok(Ok(format)) => format,
             Ok(Err(e)) => {
                 error!("Meetwings Audio initialization failed: {}", e);
-                44100
+                consumer.handle().record_error(&e);
+                consumer.handle().close();
+                (44100, 1)
             }
             Err(_) => {
                 error!("Meetwings Audio initialization timeout");
-                44100
+                consumer
+                    .handle()
+                    .record_error("Audio initialization timeout");
+                consumer.handle().close();
+                (44100, 1)
             }
         };
 
         SpeakerStream {
-            sample_queue,
-            waker_state,
+            queue: consumer,
             capture_thread: Some(capture_thread),
             actual_sample_rate,
+            channels,
         }
     }
 }
 
-struct WakerState {
-    waker: Option<Waker>,
-    has_data: bool,
-    shutdown: bool,
-}
-
 pub struct SpeakerStream {
-    sample_queue: Arc<Mutex<VecDeque<f32>>>,
-    waker_state: Arc<Mutex<WakerState>>,
+    queue: SampleConsumer,
     capture_thread: Option<thread::JoinHandle<()>>,
     actual_sample_rate: u32,
+    channels: usize,
 }
 
 impl SpeakerStream {
@@ -84,146 +153,307 @@ impl SpeakerStream {
         self.actual_sample_rate
     }
 
-    fn capture_audio_loop(
-        sample_queue: Arc<Mutex<VecDeque<f32>>>,
-        waker_state: Arc<Mutex<WakerState>>,
-        init_tx: mpsc::Sender<Result<u32>>,
-        device_index: Option<usize>,
-    ) -> Result<()> {
-        let init_result = (|| -> Result<_> {
-            let device = match device_index {
-                Some(index) => {
-                    use wasapi::DeviceCollection;
-                    let collection = DeviceCollection::new(&Direction::Render)?;
-                    collection.get_device_at_index(index.try_into()?)?
-                }
-                None => get_default_device(&Direction::Render)?,
-            };
-            let mut audio_client = device.get_iaudioclient()?;
+    pub fn backend(&self) -> &'static str {
+        "wasapi"
+    }
 
-            let device_format = audio_client.get_mixformat()?;
-            let actual_rate = device_format.get_samplespersec();
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
 
-            let desired_format =
-                WaveFormat::new(32, 32, &SampleType::Float, actual_rate as usize, 1, None);
+    pub fn queue(&self) -> &QueueHandle {
+        self.queue.handle()
+    }
 
-            let (_def_time, min_time) = audio_client.get_device_period()?;
+    pub fn poll_frame(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+        frame_size: usize,
+    ) -> Poll<Option<AudioFrame>> {
+        self.queue
+            .poll_frame(cx, frame_size, self.actual_sample_rate)
+    }
 
-            let mode = StreamMode::EventsShared {
-                autoconvert: true,
-                buffer_duration_hns: min_time,
-            };
+    fn capture_audio_loop(
+        mut producer: SampleProducer,
+        init_tx: mpsc::Sender<Result<(u32, usize)>>,
+        device_index: Option<usize>,
+        on_device_event: Option<DeviceEventCallback>,
+        channel_mode: ChannelMode,
+    ) -> Result<()> {
+        let notify = |event: DeviceEvent| {
+            if let Some(callback) = &on_device_event {
+                callback(event);
+            }
+        };
 
-            audio_client.initialize_client(&desired_format, &Direction::Capture, &mode)?;
+        // Plain averaging is left to WASAPI; other modes need every channel
+        let requested_channels = (!channel_mode.needs_native_channels()).then_some(1);
 
-            let h_event = audio_client.set_get_eventhandle()?;
-            let render_client = audio_client.get_audiocaptureclient()?;
+        let endpoint = match device_index {
+            Some(index) => Endpoint::Index(index),
+            None => Endpoint::Default,
+        };
+        let mut capture = match CaptureSession::open(endpoint, None, requested_channels) {
+            Ok(capture) => {
+                let output_channels = channel_mode.output_channels(capture.channels as usize);
+                let _ = init_tx.send(Ok((capture.sample_rate, output_channels)));
+                producer.handle().set_source(&capture.device_name);
+                capture
+            }
+            Err(e) => {
+                let _ = init_tx.send(Err(e));
+                return Ok(());
+            }
+        };
 
-            audio_client.start_stream()?;
+        // Reopened endpoints keep the original format (WASAPI autoconvert converts)
+        let sample_rate = capture.sample_rate;
+        let channels = capture.channels;
+        let follow_default = device_index.is_none();
+        let chosen_id = device_index.map(|_| capture.device_id.clone());
+        let mut last_default_check = Instant::now();
 
-            Ok((h_event, render_client, actual_rate))
-        })();
+        loop {
+            if producer.handle().is_closed() {
+                break;
+            }
 
-        match init_result {
-            Ok((h_event, render_client, sample_rate)) => {
-                let _ = init_tx.send(Ok(sample_rate));
+            // Default render endpoint switched (e.g. headset plugged in)
+            if follow_default && last_default_check.elapsed() >= DEFAULT_DEVICE_POLL_INTERVAL {
+                last_default_check = Instant::now();
+                let default_id = get_default_device(&Direction::Render)
+                    .and_then(|device| device.get_id())
+                    .ok();
 
-                loop {
+                if default_id.is_some() && default_id.as_ref() != Some(&capture.device_id) {
+                    match CaptureSession::open(Endpoint::Default, Some(sample_rate), Some(channels))
                     {
-                        let state = waker_state.lock().unwrap();
-                        if state.shutdown {
-                            break;
+                        Ok(reopened) => {
+                            capture = reopened;
+                            producer.handle().set_source(&capture.device_name);
+                            notify(DeviceEvent::Changed {
+                                device: capture.device_name.clone(),
+                            });
+                        }
+                        Err(e) => {
+                            warn!("Failed to follow default device change: {}", e);
+                            producer.handle().record_error(&e);
                         }
                     }
+                }
+            }
 
-                    if h_event.wait_for_event(3000).is_err() {
-                        error!("Meetwings timeout error, stopping capture");
-                        break;
+            let read_result = capture.read_samples();
+            let samples = match read_result {
+                Ok(samples) => samples,
+                Err(e) => {
+                    error!("Meetwings capture device error: {}", e);
+                    producer.handle().record_error(&e);
+                    notify(DeviceEvent::Lost {
+                        device: capture.device_name.clone(),
+                        error: e.to_string(),
+                    });
+
+                    // Retry the chosen endpoint, or whatever is now the default
+                    match reopen(
+                        chosen_id.as_deref(),
+                        sample_rate,
+                        channels,
+                        producer.handle(),
+                    ) {
+                        Some(reopened) => {
+                            capture = reopened;
+                            producer.handle().set_source(&capture.device_name);
+                            notify(DeviceEvent::Changed {
+                                device: capture.device_name.clone(),
+                            });
+                            continue;
+                        }
+                        None => {
+                            error!("Meetwings giving up on capture after device loss");
+                            break;
+                        }
                     }
+                }
+            };
+            let samples = channel_mode.apply(&samples, channels as usize);
 
-                    let mut temp_queue = VecDeque::new();
-                    if let Err(e) = render_client.read_from_device_to_deque(&mut temp_queue) {
-                        error!("Meetwings Failed to read audio data: {}", e);
-                        continue;
-                    }
+            // Overflow is counted by the queue (see QueueStats)
+            producer.push(&samples);
+        }
 
-                    if temp_queue.is_empty() {
-                        continue;
-                    }
+        // End the stream so consumers see the capture finish
+        producer.handle().close();
 
-                    let mut samples = Vec::new();
-                    while temp_queue.len() >= 4 {
-                        let bytes = [
-                            temp_queue.pop_front().unwrap(),
-                            temp_queue.pop_front().unwrap(),
-                            temp_queue.pop_front().unwrap(),
-                            temp_queue.pop_front().unwrap(),
-                        ];
-                        let sample = f32::from_le_bytes(bytes);
-                        samples.push(sample);
-                    }
+        Ok(())
+    }
+}
 
-                    if !samples.is_empty() {
-                        // Consistent buffer overflow handling
-                        let dropped = {
-                            let mut queue = sample_queue.lock().unwrap();
-                            let max_buffer_size = 131072; // 128KB buffer (matching macOS)
+// Which render endpoint CaptureSession::open captures
+enum Endpoint<'a> {
+    Default,
+    Index(usize), // Position in list_devices; only stable until devices change
+    Id(&'a str),  // Endpoint ID, for finding a chosen device again after a replug
+}
 
-                            queue.extend(samples.iter());
+// One opened loopback capture on a render endpoint
+struct CaptureSession {
+    _audio_client: AudioClient,
+    h_event: Handle,
+    capture_client: AudioCaptureClient,
+    device_id: String,
+    device_name: String,
+    sample_rate: u32,
+    channels: u16,
+}
 
-                            // If buffer exceeds maximum, drop oldest samples
-                            let dropped_count = if queue.len() > max_buffer_size {
-                                let to_drop = queue.len() - max_buffer_size;
-                                queue.drain(0..to_drop);
-                                to_drop
-                            } else {
-                                0
-                            };
+impl CaptureSession {
+    // Opens the endpoint; None keeps the device's own rate / channel count
+    fn open(endpoint: Endpoint, sample_rate: Option<u32>, channels: Option<u16>) -> Result<Self> {
+        use wasapi::DeviceCollection;
 
-                            dropped_count
-                        };
+        let device = match endpoint {
+            Endpoint::Default => get_default_device(&Direction::Render)?,
+            Endpoint::Index(index) => {
+                let collection = DeviceCollection::new(&Direction::Render)?;
+                collection.get_device_at_index(index.try_into()?)?
+            }
+            Endpoint::Id(id) => {
+                let collection = DeviceCollection::new(&Direction::Render)?;
+                (0..collection.get_nbr_devices()?)
+                    .filter_map(|index| collection.get_device_at_index(index).ok())
+                    .find(|device| device.get_id().is_ok_and(|device_id| device_id == id))
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("Selected audio device is no longer available")
+                    })?
+            }
+        };
+        let device_id = device.get_id()?;
+        let device_name = device
+            .get_friendlyname()
+            .unwrap_or_else(|_| device_id.clone());
+        let mut audio_client = device.get_iaudioclient()?;
+
+        let device_format = audio_client.get_mixformat()?;
+        let actual_rate = sample_rate.unwrap_or(device_format.get_samplespersec());
+        let channels = channels.unwrap_or(device_format.get_nchannels()).max(1);
+
+        let desired_format = WaveFormat::new(
+            32,
+            32,
+            &SampleType::Float,
+            actual_rate as usize,
+            channels as usize,
+            None,
+        );
+
+        let (_def_time, min_time) = audio_client.get_device_period()?;
+
+        let mode = StreamMode::EventsShared {
+            autoconvert: true,
+            buffer_duration_hns: min_time,
+        };
 
-                        if dropped > 0 {
-                            error!("Windows buffer overflow - dropped {} samples", dropped);
-                        }
+        audio_client.initialize_client(&desired_format, &Direction::Capture, &mode)?;
 
-                        // Wake up consumer
-                        {
-                            let mut state = waker_state.lock().unwrap();
-                            if !state.has_data {
-                                state.has_data = true;
-                                if let Some(waker) = state.waker.take() {
-                                    drop(state);
-                                    waker.wake();
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+        let h_event = audio_client.set_get_eventhandle()?;
+        let capture_client = audio_client.get_audiocaptureclient()?;
+
+        audio_client.start_stream()?;
+
+        Ok(Self {
+            _audio_client: audio_client,
+            h_event,
+            capture_client,
+            device_id,
+            device_name,
+            sample_rate: actual_rate,
+            channels,
+        })
+    }
+
+    // Waits for the next packet; empty when nothing is playing. Errors mean the endpoint
+    // stopped delivering audio (e.g. AUDCLNT_E_DEVICE_INVALIDATED).
+    fn read_samples(&self) -> Result<Vec<f32>> {
+        match self.h_event.wait_for_event(PACKET_WAIT_MS) {
+            Ok(()) => {}
+            Err(WasapiError::EventTimeout) => return Ok(Vec::new()),
+            Err(e) => return Err(anyhow::anyhow!("Failed to wait for audio: {}", e)),
+        }
+
+        let mut temp_queue = VecDeque::new();
+        self.capture_client
+            .read_from_device_to_deque(&mut temp_queue)
+            .map_err(|e| anyhow::anyhow!("Failed to read audio data: {}", e))?;
+
+        let mut samples = Vec::with_capacity(temp_queue.len() / 4);
+        while temp_queue.len() >= 4 {
+            let bytes = [
+                temp_queue.pop_front().unwrap(),
+                temp_queue.pop_front().unwrap(),
+                temp_queue.pop_front().unwrap(),
+                temp_queue.pop_front().unwrap(),
+            ];
+            samples.push(f32::from_le_bytes(bytes));
+        }
+
+        Ok(samples)
+    }
+}
+
+// Retries the endpoint (None: the default) with backoff after a device loss. A chosen
+// endpoint is found again by its ID, since indexes shift when devices are removed.
+fn reopen(
+    chosen_id: Option<&str>,
+    sample_rate: u32,
+    channels: u16,
+    queue: &QueueHandle,
+) -> Option<CaptureSession> {
+    for attempt in 1..=REOPEN_ATTEMPTS {
+        if queue.is_closed() {
+            return None;
+        }
+
+        thread::sleep(REOPEN_BACKOFF);
+
+        let endpoint = match chosen_id {
+            Some(id) => Endpoint::Id(id),
+            None => Endpoint::Default,
+        };
+        match CaptureSession::open(endpoint, Some(sample_rate), Some(channels)) {
+            Ok(capture) => return Some(capture),
             Err(e) => {
-                let _ = init_tx.send(Err(e));
-                return Ok(());
+                warn!(
+                    "Meetwings reopen attempt {}/{} failed: {}",
+                    attempt, REOPEN_ATTEMPTS, e
+                );
+                queue.record_error(&e);
             }
         }
-
-        Ok(())
     }
+
+    None
 }
 
 // Drops the audio stream
 impl Drop for SpeakerStream {
     fn drop(&mut self) {
-        {
-            let mut state = self.waker_state.lock().unwrap();
-            state.shutdown = true;
-        }
+        self.queue.handle().close();
 
         if let Some(thread) = self.capture_thread.take() {
             if let Err(e) = thread.join() {
                 error!("Failed to join capture thread: {:?}", e);
             }
         }
+
+        let stats = self.queue.handle().stats();
+        if stats.dropped > 0 {
+            warn!(
+                "System audio capture dropped {} of {} samples ({} overflows)",
+                stats.dropped, stats.received, stats.overflows
+            );
+        }
     }
 }
 
@@ -233,39 +463,9 @@ impl Stream for SpeakerStream {
 
     // Polls the audio stream
     fn poll_next(
-        self: std::pin::Pin<&mut Self>,
+        mut self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> Poll<Option<Self::Item>> {
-        {
-            let state = self.waker_state.lock().unwrap();
-            if state.shutdown {
-                return Poll::Ready(None);
-            }
-        }
-
-        {
-            let mut queue = self.sample_queue.lock().unwrap();
-            if let Some(sample) = queue.pop_front() {
-                return Poll::Ready(Some(sample));
-            }
-        }
-
-        {
-            let mut state = self.waker_state.lock().unwrap();
-            if state.shutdown {
-                return Poll::Ready(None);
-            }
-            state.has_data = false;
-            state.waker = Some(cx.waker().clone());
-            drop(state);
-        }
-
-        {
-            let mut queue = self.sample_queue.lock().unwrap();
-            match queue.pop_front() {
-                Some(sample) => Poll::Ready(Some(sample)),
-                None => Poll::Pending,
-            }
-        }
+        self.queue.poll_sample(cx)
     }
 }
@@ -13,9 +13,10 @@
 import { useEffect, useCallback, useRef, useState } from 'react';
 import { listen } from '@tauri-apps/api/event';
 import { invoke } from '@tauri-apps/api/core';
-import { fetchSTT } from '@/lib';
+import { fetchSTT, loadAudioSegment, releaseAudioSegment } from '@/lib';
 import type { TYPE_PROVIDER } from '@/types';
 import type { DiarizationAudioBuffer } from '@/lib/functions/audio-buffer';
+import type { SpeechSegment } from './useSystemAudio';
 
 interface SelectedProvider {
   provider: string;
@@ -69,7 +70,7 @@ export function useMeetingAudio({
   audioBuffer,
 }: UseMeetingAudioProps) {
   // Queue-based processing to avoid dropping speech segments
-  const processingQueueRef = useRef<string[]>([]);
+  const processingQueueRef = useRef<number[]>([]); // Backend segment IDs
   const isProcessingRef = useRef(false);
   const [isProcessing, setIsProcessing] = useState(false);
   const [hasQueuedAudio, setHasQueuedAudio] = useState(false);
@@ -109,30 +110,27 @@ export function useMeetingAudio({
           // Notify user about discarded segments
           onErrorRef.current?.(new Error(`Meeting mode stopped. ${discardedCount} audio segment${discardedCount > 1 ? 's were' : ' was'} not transcribed.`));
         }
+        processingQueueRef.current.forEach(releaseAudioSegment);
         processingQueueRef.current = [];
         setHasQueuedAudio(false);
         break;
       }
 
-      const base64Audio = processingQueueRef.current.shift()!;
+      const segmentId = processingQueueRef.current.shift()!;
 
       // Update queue state
       setHasQueuedAudio(processingQueueRef.current.length > 0);
 
       try {
-        // Convert base64 to blob
-        const binaryString = atob(base64Audio);
-        const bytes = new Uint8Array(binaryString.length);
-        for (let i = 0; i < binaryString.length; i++) {
-          bytes[i] = binaryString.charCodeAt(i);
-        }
-        const audioBlob = new Blob([bytes], { type: 'audio/wav' });
+        // Raw bytes, kept for the diarization buffer
+        const audioBlob = await loadAudioSegment(segmentId);
 
         // Transcribe
         const transcription = await fetchSTT({
           provider: sttProvider,
           selectedProvider: selectedSttProvider,
           audio: audioBlob,
+          segmentId,
           language: sttLanguage,
         });
 
@@ -155,6 +153,8 @@ export function useMeetingAudio({
         console.error('[MeetingAudio] STT failed:', err);
         // Don't call onError for individual STT failures - just log
         // This prevents flooding the user with errors during transient issues
+      } finally {
+        releaseAudioSegment(segmentId);
       }
     }
 
@@ -208,14 +208,14 @@ export function useMeetingAudio({
         });
 
         // Listen for speech detected events (fires when speech ends)
-        unlistenSpeechDetected = await listen('speech-detected', async (event) => {
+        unlistenSpeechDetected = await listen<SpeechSegment>('speech-detected', async (event) => {
           if (!enabledRef.current) return;
 
-          const base64Audio = event.payload as string;
+          const { segment_id: segmentId } = event.payload;
 
           // Prevent unbounded queue growth when STT is slower than audio capture
           if (processingQueueRef.current.length >= MAX_QUEUE_SIZE) {
-            processingQueueRef.current.shift(); // Drop oldest segment
+            releaseAudioSegment(processingQueueRef.current.shift()!); // Drop oldest segment
             droppedCountRef.current++;
             console.warn('[MeetingAudio] Queue full, dropping oldest segment');
 
@@ -228,7 +228,7 @@ export function useMeetingAudio({
           }
 
           // Queue instead of dropping
-          processingQueueRef.current.push(base64Audio);
+          processingQueueRef.current.push(segmentId);
           processQueue();
         });
 
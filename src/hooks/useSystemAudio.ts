@@ -3,7 +3,15 @@ import { useWindowResize, useGlobalShortcuts } from ".";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import { useApp } from "@/contexts";
-import { fetchSTT, fetchAIResponse, summarizeConversation, shouldSummarize } from "@/lib/functions";
+import {
+  fetchSTT,
+  fetchAIResponse,
+  summarizeConversation,
+  shouldSummarize,
+  loadAudioSegment,
+  releaseAudioSegment,
+  mergeOverlappingText,
+} from "@/lib/functions";
 import {
   DEFAULT_QUICK_ACTIONS,
   DEFAULT_SYSTEM_PROMPT,
@@ -31,6 +39,56 @@ export interface VadConfig {
   pre_speech_chunks: number;
   noise_gate_threshold: number;
   max_recording_duration_secs: number;
+  target_sample_rate?: number | null; // Segment encode rate (null = device native)
+  downmix?: "average" | "left" | "right" | "max_energy"; // Multichannel -> mono strategy
+  level_events?: boolean; // Emit throttled audio-level events
+  level_interval_ms?: number;
+  vad_engine?: "energy" | "spectral"; // Speech detector
+  adaptive_thresholds?: boolean; // Derive thresholds from the measured noise floor
+  adaptive_speech_ratio?: number;
+  adaptive_gate_ratio?: number;
+  noise_suppression?: boolean; // STFT noise suppression on speech segments
+  noise_suppression_strength?: number;
+  noise_suppression_floor_db?: number;
+  partial_segments?: boolean; // Emit rolling speech-partial events during speech
+  partial_interval_ms?: number;
+  partial_overlap_ms?: number;
+  encoding?: "wav" | "flac" | "opus"; // Segment container; opus needs the `opus` build feature
+  chunked_continuous?: boolean; // No length limit; emit overlapping continuous-chunk events
+  continuous_chunk_secs?: number;
+  continuous_chunk_overlap_ms?: number;
+}
+
+// Backend-held audio segment. The audio stays in the backend: fetch it with
+// loadAudioSegment, free it with releaseAudioSegment.
+export interface AudioSegment {
+  segment_id: number;
+  encoding: "wav" | "flac" | "opus";
+  duration_ms: number;
+  size_bytes: number;
+}
+
+// speech-detected payload, the same for system, mic and dual captures
+export interface SpeechSegment extends AudioSegment {
+  source: "mic" | "system";
+  start_ms: number; // Unix epoch milliseconds of the first sample
+}
+
+// continuous-chunk payload (VadConfig.chunked_continuous); the is_final chunk ends the recording
+export interface ContinuousChunk extends AudioSegment {
+  sequence: number;
+  is_final: boolean;
+  offset_ms: number; // Position in the recording, excluding paused time
+  overlap_ms: number; // Leading audio repeated from the previous chunk
+}
+
+// speech-partial payload; the is_final segment closes the utterance
+export interface PartialSegment extends AudioSegment {
+  utterance_id: number;
+  sequence: number;
+  is_final: boolean;
+  source: "mic" | "system" | null;
+  start_ms: number;
 }
 
 // OPTIMIZED VAD defaults - matches backend exactly for perfect performance
@@ -85,6 +143,7 @@ export function useSystemAudio() {
   const [isContinuousMode, setIsContinuousMode] = useState<boolean>(false);
   const [isRecordingInContinuousMode, setIsRecordingInContinuousMode] =
     useState<boolean>(false);
+  const [isContinuousPaused, setIsContinuousPaused] = useState<boolean>(false);
   const [stream, setStream] = useState<MediaStream | null>(null); // for audio visualizer
   const streamRef = useRef<MediaStream | null>(null);
 
@@ -164,12 +223,14 @@ export function useSystemAudio() {
     let progressUnlisten: (() => void) | undefined;
     let startUnlisten: (() => void) | undefined;
     let stopUnlisten: (() => void) | undefined;
+    let pausedUnlisten: (() => void) | undefined;
+    let resumedUnlisten: (() => void) | undefined;
     let errorUnlisten: (() => void) | undefined;
     let discardedUnlisten: (() => void) | undefined;
 
     const setupContinuousListeners = async () => {
       try {
-        // Progress updates (every second)
+        // Progress updates (every recorded second; paused time is not counted)
         progressUnlisten = await listen("recording-progress", (event) => {
           const seconds = event.payload as number;
           setRecordingProgress(seconds);
@@ -178,15 +239,25 @@ export function useSystemAudio() {
         // Recording started
         startUnlisten = await listen("continuous-recording-start", () => {
           setRecordingProgress(0);
+          setIsContinuousPaused(false);
           setIsRecordingInContinuousMode(true);
         });
 
         // Recording stopped
         stopUnlisten = await listen("continuous-recording-stopped", () => {
           setRecordingProgress(0);
+          setIsContinuousPaused(false);
           setIsRecordingInContinuousMode(false);
         });
 
+        // Recording paused / resumed
+        pausedUnlisten = await listen("continuous-recording-paused", () => {
+          setIsContinuousPaused(true);
+        });
+        resumedUnlisten = await listen("continuous-recording-resumed", () => {
+          setIsContinuousPaused(false);
+        });
+
         // Audio encoding errors
         errorUnlisten = await listen("audio-encoding-error", (event) => {
           const errorMsg = event.payload as string;
@@ -214,6 +285,8 @@ export function useSystemAudio() {
       if (progressUnlisten) progressUnlisten();
       if (startUnlisten) startUnlisten();
       if (stopUnlisten) stopUnlisten();
+      if (pausedUnlisten) pausedUnlisten();
+      if (resumedUnlisten) resumedUnlisten();
       if (errorUnlisten) errorUnlisten();
       if (discardedUnlisten) discardedUnlisten();
     };
@@ -225,18 +298,12 @@ export function useSystemAudio() {
 
     const setupEventListener = async () => {
       try {
-        speechUnlisten = await listen("speech-detected", async (event) => {
+        speechUnlisten = await listen<SpeechSegment>("speech-detected", async (event) => {
+          const { segment_id: segmentId } = event.payload;
           try {
             if (!capturing) return;
 
-            const base64Audio = event.payload as string;
-            // Convert to blob
-            const binaryString = atob(base64Audio);
-            const bytes = new Uint8Array(binaryString.length);
-            for (let i = 0; i < binaryString.length; i++) {
-              bytes[i] = binaryString.charCodeAt(i);
-            }
-            const audioBlob = new Blob([bytes], { type: "audio/wav" });
+            const audioBlob = await loadAudioSegment(segmentId);
 
             const useMeetwingsAPI = await shouldUseMeetwingsAPI();
             if (!selectedSttProvider.provider && !useMeetwingsAPI) {
@@ -260,6 +327,7 @@ export function useSystemAudio() {
               provider: providerConfig,
               selectedProvider: selectedSttProvider,
               audio: audioBlob,
+              segmentId,
               language: sttLanguage,
             });
 
@@ -305,6 +373,7 @@ export function useSystemAudio() {
             setError("Failed to process speech");
           } finally {
             setIsProcessing(false);
+            releaseAudioSegment(segmentId);
           }
         });
       } catch (err) {
@@ -440,6 +509,7 @@ export function useSystemAudio() {
       setRecordingProgress(0);
       setIsProcessing(false);
       setIsRecordingInContinuousMode(false);
+      setIsContinuousPaused(false);
     } catch (err) {
       console.error("Failed to ignore recording:", err);
       setError(`Failed to ignore recording: ${err}`);
@@ -529,6 +599,118 @@ export function useSystemAudio() {
     [selectedAIProvider, allAiProviders, conversation.messages]
   );
 
+  // Latest values for the chunk listener below, read through a ref so it stays subscribed
+  // for the whole capture and a settings change can't drop the transcript built so far
+  const chunkContextRef = useRef({
+    selectedSttProvider,
+    allSttProviders,
+    sttLanguage,
+    useSystemPrompt,
+    systemPrompt,
+    contextContent,
+    messages: conversation.messages,
+    processWithAI,
+  });
+  useEffect(() => {
+    chunkContextRef.current = {
+      selectedSttProvider,
+      allSttProviders,
+      sttLanguage,
+      useSystemPrompt,
+      systemPrompt,
+      contextContent,
+      messages: conversation.messages,
+      processWithAI,
+    };
+  });
+
+  // Chunked continuous recordings: transcribe chunks as they arrive (in order) and
+  // send the merged transcript once the final chunk is in
+  useEffect(() => {
+    let chunkUnlisten: (() => void) | undefined;
+    let pending: Promise<void> = Promise.resolve();
+    let transcript = "";
+
+    const transcribeChunk = async (chunk: ContinuousChunk) => {
+      try {
+        const { selectedSttProvider, allSttProviders, sttLanguage } =
+          chunkContextRef.current;
+        const providerConfig = allSttProviders.find(
+          (p) => p.id === selectedSttProvider.provider
+        );
+        const text = await fetchSTT({
+          provider: providerConfig,
+          selectedProvider: selectedSttProvider,
+          audio: await loadAudioSegment(chunk.segment_id),
+          segmentId: chunk.segment_id,
+          language: sttLanguage,
+        });
+        transcript = mergeOverlappingText(transcript, text.trim());
+        setLastTranscription(transcript);
+      } catch (err) {
+        console.error("Chunk transcription failed:", err);
+        setError(`Failed to transcribe part of the recording: ${err}`);
+      } finally {
+        releaseAudioSegment(chunk.segment_id);
+      }
+
+      if (!chunk.is_final) return;
+
+      const fullTranscript = transcript;
+      transcript = "";
+      try {
+        if (!fullTranscript.trim()) {
+          setError("Received empty transcription");
+          return;
+        }
+
+        const {
+          useSystemPrompt,
+          systemPrompt,
+          contextContent,
+          messages,
+          processWithAI,
+        } = chunkContextRef.current;
+        const effectiveSystemPrompt = useSystemPrompt
+          ? systemPrompt || DEFAULT_SYSTEM_PROMPT
+          : contextContent || DEFAULT_SYSTEM_PROMPT;
+        const previousMessages = messages.map((msg) => {
+          return { role: msg.role, content: msg.content };
+        });
+
+        await processWithAI(
+          fullTranscript,
+          effectiveSystemPrompt,
+          previousMessages
+        );
+      } finally {
+        setIsProcessing(false);
+      }
+    };
+
+    const setupChunkListener = async () => {
+      try {
+        chunkUnlisten = await listen<ContinuousChunk>(
+          "continuous-chunk",
+          (event) => {
+            if (!capturing) return;
+            const chunk = event.payload;
+            setIsProcessing(true);
+            pending = pending.then(() => transcribeChunk(chunk));
+          }
+        );
+      } catch (err) {
+        setError("Failed to setup recording chunk listener");
+      }
+    };
+
+    setupChunkListener();
+
+    return () => {
+      if (chunkUnlisten) chunkUnlisten();
+    };
+  }, [capturing]);
+
   const startCapture = useCallback(async () => {
     try {
       setError("");
@@ -655,6 +837,25 @@ export function useSystemAudio() {
     }
   }, [conversation.id, conversation.messages, selectedAIProvider, allAiProviders]);
 
+  // Pause / resume continuous recording without ending it
+  const pauseContinuous = useCallback(async () => {
+    try {
+      if (!isContinuousMode || !isRecordingInContinuousMode) return;
+      await invoke("pause_continuous");
+    } catch (err) {
+      setError(`Failed to pause recording: ${err}`);
+    }
+  }, [isContinuousMode, isRecordingInContinuousMode]);
+
+  const resumeContinuous = useCallback(async () => {
+    try {
+      if (!isContinuousMode || !isRecordingInContinuousMode) return;
+      await invoke("resume_continuous");
+    } catch (err) {
+      setError(`Failed to resume recording: ${err}`);
+    }
+  }, [isContinuousMode, isRecordingInContinuousMode]);
+
   // Manual stop for continuous recording
   const manualStopAndSend = useCallback(async () => {
     try {
@@ -969,8 +1170,11 @@ export function useSystemAudio() {
     // Continuous recording
     isContinuousMode,
     isRecordingInContinuousMode,
+    isContinuousPaused,
     recordingProgress,
     manualStopAndSend,
+    pauseContinuous,
+    resumeContinuous,
     startContinuousRecording,
     ignoreContinuousRecording,
     // Scroll area ref for keyboard navigation
@@ -0,0 +1,131 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen, UnlistenFn } from "@tauri-apps/api/event";
+import type { VadConfig } from "@/hooks/useSystemAudio";
+
+// audio-import-started / audio-import-progress payload
+export interface AudioImportProgress {
+  import_id: number;
+  decoded_ms: number;
+  duration_ms: number | null; // null when the file doesn't state its length
+  segments_found: number;
+  segments_transcribed: number;
+}
+
+// audio-import-segment payload, in file order
+export interface ImportedSegment {
+  import_id: number;
+  index: number;
+  start_ms: number; // Offset into the file
+  duration_ms: number;
+  transcription: string;
+}
+
+// audio-import-finished payload
+export interface AudioImportResult {
+  import_id: number;
+  status: "completed" | "cancelled" | "failed";
+  segments_transcribed: number;
+  error: string | null;
+}
+
+export interface AudioImportHandlers {
+  onProgress?: (progress: AudioImportProgress) => void;
+  onSegment?: (segment: ImportedSegment) => void;
+}
+
+export interface AudioImport {
+  importId: number;
+  cancel: () => Promise<void>;
+  finished: Promise<AudioImportResult>;
+}
+
+type ImportEvent =
+  | { kind: "progress"; payload: AudioImportProgress }
+  | { kind: "segment"; payload: ImportedSegment }
+  | { kind: "finished"; payload: AudioImportResult };
+
+/**
+ * Decodes, splits and transcribes an audio or video file on disk
+ * (WAV, MP3, M4A/MP4, Ogg Vorbis, FLAC). Resolves once the import has started;
+ * segments and progress arrive through the handlers until `finished` settles.
+ */
+export async function importAudioFile(
+  path: string,
+  handlers: AudioImportHandlers = {},
+  vadConfig?: VadConfig
+): Promise<AudioImport> {
+  let importId: number | null = null;
+  const early: ImportEvent[] = []; // Events that arrive before the command returns its ID
+  let resolveFinished: (result: AudioImportResult) => void = () => {};
+  const finished = new Promise<AudioImportResult>((resolve) => {
+    resolveFinished = resolve;
+  });
+
+  const unlisteners: UnlistenFn[] = [];
+  const stop = () => unlisteners.forEach((unlisten) => unlisten());
+
+  const dispatch = (event: ImportEvent) => {
+    if (event.payload.import_id !== importId) return;
+    switch (event.kind) {
+      case "progress":
+        handlers.onProgress?.(event.payload);
+        break;
+      case "segment":
+        handlers.onSegment?.(event.payload);
+        break;
+      case "finished":
+        stop();
+        resolveFinished(event.payload);
+        break;
+    }
+  };
+  const receive = (event: ImportEvent) =>
+    importId === null ? early.push(event) : dispatch(event);
+
+  unlisteners.push(
+    await listen<AudioImportProgress>("audio-import-started", (e) =>
+      receive({ kind: "progress", payload: e.payload })
+    ),
+    await listen<AudioImportProgress>("audio-import-progress", (e) =>
+      receive({ kind: "progress", payload: e.payload })
+    ),
+    await listen<ImportedSegment>("audio-import-segment", (e) =>
+      receive({ kind: "segment", payload: e.payload })
+    ),
+    await listen<AudioImportResult>("audio-import-finished", (e) =>
+      receive({ kind: "finished", payload: e.payload })
+    )
+  );
+
+  try {
+    importId = await invoke<number>("import_audio_file", {
+      path,
+      vadConfig: vadConfig ?? null,
+    });
+  } catch (error) {
+    stop();
+    throw error;
+  }
+  early.splice(0).forEach(dispatch);
+
+  const id = importId;
+  return {
+    importId: id,
+    cancel: () => invoke("cancel_audio_import", { importId: id }),
+    finished,
+  };
+}
+
+// Joins imported segments into a transcript with [mm:ss] offsets
+export function formatImportedTranscript(segments: ImportedSegment[]): string {
+  return [...segments]
+    .sort((a, b) => a.index - b.index)
+    .map((segment) => {
+      const seconds = Math.floor(segment.start_ms / 1000);
+      const stamp = `${String(Math.floor(seconds / 60)).padStart(2, "0")}:${String(
+        seconds % 60
+      ).padStart(2, "0")}`;
+      return `[${stamp}] ${segment.transcription}`;
+    })
+    .join("\n");
+}
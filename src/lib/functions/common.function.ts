@@ -1,3 +1,4 @@
+import { invoke } from "@tauri-apps/api/core";
 import { Message, UsageData } from "@/types";
 
 export function getByPath(obj: any, path: string): any {
@@ -32,6 +33,56 @@ export async function blobToBase64(blob: Blob): Promise<string> {
   });
 }
 
+// Container of a speech segment (see VadConfig.encoding), sniffed from its magic bytes
+export function audioMimeType(bytes: Uint8Array): string {
+  const magic = String.fromCharCode(...bytes.subarray(0, 4));
+  if (magic === "fLaC") return "audio/flac";
+  if (magic === "OggS") return "audio/ogg";
+  return "audio/wav";
+}
+
+export function audioFileName(mimeType: string): string {
+  if (mimeType === "audio/flac") return "audio.flac";
+  if (mimeType === "audio/ogg") return "audio.ogg";
+  return "audio.wav";
+}
+
+// Joins transcripts of overlapping audio chunks, dropping the words the overlap repeated
+export function mergeOverlappingText(
+  previous: string,
+  next: string,
+  maxWords = 12
+): string {
+  if (!previous) return next;
+  if (!next) return previous;
+
+  const normalize = (word: string) =>
+    word.toLowerCase().replace(/[^\p{L}\p{N}']/gu, "");
+  const prevWords = previous.split(/\s+/);
+  const nextWords = next.split(/\s+/);
+
+  const longest = Math.min(maxWords, prevWords.length, nextWords.length);
+  for (let n = longest; n > 0; n--) {
+    const tail = prevWords.slice(-n).map(normalize).join(" ");
+    const head = nextWords.slice(0, n).map(normalize).join(" ");
+    if (tail === head) {
+      return [...prevWords, ...nextWords.slice(n)].join(" ");
+    }
+  }
+  return `${previous} ${next}`;
+}
+
+// Fetches a speech segment held by the backend as raw bytes (no base64)
+export async function loadAudioSegment(segmentId: number): Promise<Blob> {
+  const buffer = await invoke<ArrayBuffer>("get_audio_segment", { segmentId });
+  const bytes = new Uint8Array(buffer);
+  return new Blob([bytes], { type: audioMimeType(bytes) });
+}
+
+export function releaseAudioSegment(segmentId: number): void {
+  invoke("release_audio_segment", { segmentId }).catch(() => {});
+}
+
 export function extractVariables(
   curl: string,
   includeAll = false
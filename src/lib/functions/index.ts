@@ -8,5 +8,6 @@ export * from "./knowledge-compactor";
 export * from "./context-builder";
 export * from "./translation.function";
 export * from "./audio-buffer";
+export * from "./audio-import.function";
 export * from "./pitch-analysis";
 export * from "./api-test.function";
@@ -2,6 +2,7 @@ import {
   deepVariableReplacer,
   getByPath,
   blobToBase64,
+  audioFileName,
 } from "./common.function";
 import { fetch as tauriFetch } from "@tauri-apps/plugin-http";
 import { invoke } from "@tauri-apps/api/core";
@@ -105,19 +106,23 @@ function emitSTTUsage(
 }
 
 // Meetwings STT function
-async function fetchMeetwingsSTT(audio: File | Blob): Promise<string> {
+async function fetchMeetwingsSTT(
+  audio: File | Blob,
+  segmentId?: number
+): Promise<string> {
   try {
-    // Convert audio to base64
-    const audioBase64 = await blobToBase64(audio);
+    // Backend segments are transcribed in place; other audio goes over as base64
+    const args =
+      segmentId !== undefined
+        ? { segmentId }
+        : { audioBase64: await blobToBase64(audio) };
 
     // Call Tauri command
     const response = await invoke<{
       success: boolean;
       transcription?: string;
       error?: string;
-    }>("transcribe_audio", {
-      audioBase64,
-    });
+    }>("transcribe_audio", args);
 
     if (response.success && response.transcription) {
       return response.transcription;
@@ -143,6 +148,8 @@ export interface STTParams {
   };
   /** Audio file to transcribe */
   audio: File | Blob;
+  /** Backend segment ID of `audio` (from speech-detected), so Meetwings STT skips re-sending it */
+  segmentId?: number;
   /**
    * Language code for speech recognition.
    * - ISO 639-1 code (e.g., "en", "es", "fr") for specific language
@@ -173,12 +180,12 @@ export async function fetchSTT(params: STTParams): Promise<string> {
   let warnings: string[] = [];
 
   try {
-    const { provider, selectedProvider, audio, language } = params;
+    const { provider, selectedProvider, audio, language, segmentId } = params;
 
     // Check if we should use Meetwings API instead
     const useMeetwingsAPI = await shouldUseMeetwingsAPI();
     if (useMeetwingsAPI) {
-      return await fetchMeetwingsSTT(audio);
+      return await fetchMeetwingsSTT(audio, segmentId);
     }
 
     if (!provider) throw new Error("Provider not provided");
@@ -295,7 +302,7 @@ export async function fetchSTT(params: STTParams): Promise<string> {
       const freshBlob = new Blob([await audio.arrayBuffer()], {
         type: audio.type,
       });
-      form.append("file", freshBlob, "audio.wav");
+      form.append("file", freshBlob, audioFileName(audio.type));
       const headerKeys = Object.keys(headers).map((k) =>
         k.toUpperCase().replace(/[-_]/g, "")
       );
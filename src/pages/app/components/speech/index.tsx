@@ -10,6 +10,8 @@ import {
   AlertCircleIcon,
   LoaderIcon,
   AudioLinesIcon,
+  PauseIcon,
+  PlayIcon,
 } from "lucide-react";
 import { Warning } from "./Warning";
 import { Header } from "./Header";
@@ -53,8 +55,11 @@ export const SystemAudio = (props: useSystemAudioType) => {
     updateVadConfiguration,
     isContinuousMode,
     isRecordingInContinuousMode,
+    isContinuousPaused,
     recordingProgress,
     manualStopAndSend,
+    pauseContinuous,
+    resumeContinuous,
     startContinuousRecording,
     ignoreContinuousRecording,
     scrollAreaRef,
@@ -142,6 +147,8 @@ export const SystemAudio = (props: useSystemAudioType) => {
                     <div className="flex items-start gap-3 mb-3">
                       {isProcessing || isAIProcessing ? (
                         <LoaderIcon className="w-5 h-5 animate-spin mt-0.5" />
+                      ) : isContinuousPaused ? (
+                        <PauseIcon className="w-5 h-5 mt-0.5 opacity-50" />
                       ) : isRecordingInContinuousMode ? (
                         <AudioLinesIcon className="w-5 h-5 animate-pulse mt-0.5" />
                       ) : (
@@ -151,6 +158,8 @@ export const SystemAudio = (props: useSystemAudioType) => {
                         <h4 className="font-medium text-sm mb-1">
                           {isProcessing || isAIProcessing
                             ? "Processing Your Audio..."
+                            : isContinuousPaused
+                            ? "Recording Paused"
                             : isRecordingInContinuousMode
                             ? "Recording Audio (Continuous Mode)"
                             : "Continuous Mode (Not Recording)"}
@@ -158,6 +167,11 @@ export const SystemAudio = (props: useSystemAudioType) => {
                         <p className="text-xs text-muted-foreground">
                           {isProcessing || isAIProcessing
                             ? "Transcribing and generating AI response..."
+                            : isContinuousPaused
+                            ? "Audio is not being recorded. Resume to continue the same recording."
+                            : isRecordingInContinuousMode &&
+                              vadConfig.chunked_continuous
+                            ? "Recording with no length limit, transcribing as it goes. You can stop anytime."
                             : isRecordingInContinuousMode
                             ? `Recording up to ${vadConfig.max_recording_duration_secs}s. You can stop anytime.`
                             : "Click Start to begin recording, or adjust settings below."}
@@ -172,22 +186,26 @@ export const SystemAudio = (props: useSystemAudioType) => {
                         <div className="space-y-2 mb-3">
                           <div className="flex justify-between text-xs text-muted-foreground">
                             <span>Duration: {recordingProgress}s</span>
-                            <span>
-                              Max: {vadConfig.max_recording_duration_secs}s
-                            </span>
-                          </div>
-                          <div className="w-full bg-muted rounded-full h-2">
-                            <div
-                              className="bg-primary h-2 rounded-full transition-all duration-500"
-                              style={{
-                                width: `${
-                                  (recordingProgress /
-                                    vadConfig.max_recording_duration_secs) *
-                                  100
-                                }%`,
-                              }}
-                            />
+                            {!vadConfig.chunked_continuous && (
+                              <span>
+                                Max: {vadConfig.max_recording_duration_secs}s
+                              </span>
+                            )}
                           </div>
+                          {!vadConfig.chunked_continuous && (
+                            <div className="w-full bg-muted rounded-full h-2">
+                              <div
+                                className="bg-primary h-2 rounded-full transition-all duration-500"
+                                style={{
+                                  width: `${
+                                    (recordingProgress /
+                                      vadConfig.max_recording_duration_secs) *
+                                    100
+                                  }%`,
+                                }}
+                              />
+                            </div>
+                          )}
                         </div>
                       )}
 
@@ -213,10 +231,26 @@ export const SystemAudio = (props: useSystemAudioType) => {
                             >
                               Ignore
                             </Button>
+                            <Button
+                              onClick={
+                                isContinuousPaused
+                                  ? resumeContinuous
+                                  : pauseContinuous
+                              }
+                              variant="outline"
+                              className="col-span-1"
+                            >
+                              {isContinuousPaused ? (
+                                <PlayIcon className="w-4 h-4 mr-2" />
+                              ) : (
+                                <PauseIcon className="w-4 h-4 mr-2" />
+                              )}
+                              {isContinuousPaused ? "Resume" : "Pause"}
+                            </Button>
                             <Button
                               onClick={manualStopAndSend}
                               variant="default"
-                              className="col-span-2"
+                              className="col-span-1"
                             >
                               Stop & Send
                             </Button>